@@ -1,4 +1,31 @@
 use crate::prelude::*;
+use std::io::IsTerminal;
+
+#[derive(Error, Debug)]
+pub enum AuthError {
+    #[error(
+        "No OpenAI API token found and the terminal isn't interactive. Pass --token or set XEVAL_OPENAI_API_KEY"
+    )]
+    NonInteractive,
+
+    #[error("OpenAI API token from {0} is invalid")]
+    InvalidToken(OpenAiAuthSource),
+
+    #[error("No OpenAI API token provided")]
+    NoToken,
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    OpenAi(#[from] OpenAiError),
+
+    #[error(transparent)]
+    OpenAiAuth(#[from] OpenAiAuthError),
+
+    #[error(transparent)]
+    Anyhow(#[from] anyhow::Error),
+}
 
 pub struct Auth {
     pub openai: OpenAi,
@@ -10,46 +37,136 @@ pub enum AuthState {
 }
 
 impl Auth {
-    pub async fn ensure(global: &mut Global, state: AuthState) -> Result<Self> {
-        let openai = match OpenAi::detect(&global).await? {
-            Some(openai) => {
-                if matches!(state, AuthState::New) {
-                    UiMessage::info("Using OpenAI API token stored in state");
+    /// Resolve a verified token, see `OpenAiAuth::detect` for the lookup
+    /// order. Prompts for one only as a last resort and when the terminal
+    /// is interactive.
+    pub async fn ensure(
+        cli: &Cli,
+        global: &mut Global,
+        state: AuthState,
+    ) -> Result<Self, AuthError> {
+        let profile = Self::profile(cli, global)?;
+        let config = Self::openai_config(cli, global, &profile);
+        let token = cli.token.clone().map(OpenAiToken::from);
+        let mut invalid = None;
+        if let Some(openai) = OpenAi::detect(global, Some(&profile), token, config.clone())? {
+            let source = openai.auth.source().clone();
+            match openai.verify().await? {
+                OpenAiTokenVerification::Valid => {
+                    if matches!(state, AuthState::New) {
+                        UiMessage::info(&format!(
+                            "Using OpenAI API token from {source} (profile {profile})"
+                        ));
+                    }
+                    return Ok(Self { openai });
+                }
+
+                // Explicitly provided tokens must not be silently replaced
+                OpenAiTokenVerification::Invalid { .. }
+                    if !matches!(source, OpenAiAuthSource::Stored | OpenAiAuthSource::Helper) =>
+                {
+                    return Err(AuthError::InvalidToken(source));
                 }
 
-                openai
+                OpenAiTokenVerification::Invalid { .. } => {
+                    UiMessage::warn(&format!("OpenAI API token from {source} is invalid"));
+                    invalid = Some(source);
+                }
             }
-            None => loop {
-                let token = UiOpenAiToken::inquire_token()?;
-
-                match token {
-                    Some(token) => {
-                        let openai = OpenAi::from_token(token).await?;
-                        match openai {
-                            Some(openai) => {
-                                openai.auth.persist(global)?;
-                                UiMessage::success("OpenAI API token verified & saved");
-                                break openai;
-                            }
-
-                            None => {
-                                UiMessage::warn("Token is invalid!");
-                                match UiOpenAiToken::inquire_retry()? {
-                                    true => continue,
-                                    false => {}
-                                }
-                            }
-                        }
-                    }
+        }
+
+        if !Self::interactive() {
+            return Err(invalid.map_or(AuthError::NonInteractive, AuthError::InvalidToken));
+        }
+
+        let openai = loop {
+            let Some(token) = UiOpenAiToken::inquire_token()? else {
+                return Err(AuthError::NoToken);
+            };
 
-                    None => {}
-                };
+            let auth =
+                OpenAiAuth::new(token, OpenAiAuthSource::Prompt).with_profile(global, &profile);
+            let openai = OpenAi::unverified(auth, config.clone())?;
+            match openai.verify().await? {
+                OpenAiTokenVerification::Valid => {
+                    openai.auth.persist(global)?;
+                    UiMessage::success("OpenAI API token verified & saved");
+                    break openai;
+                }
 
-                UiMessage::warn("No token provided");
-                exit(1);
-            },
+                OpenAiTokenVerification::Invalid { .. } => {
+                    UiMessage::warn("Token is invalid!");
+                    if !UiOpenAiToken::inquire_retry()? {
+                        return Err(AuthError::NoToken);
+                    }
+                }
+            }
         };
 
         Ok(Self { openai })
     }
+
+    /// Whether we can prompt the user.
+    pub fn interactive() -> bool {
+        std::io::stdin().is_terminal()
+    }
+
+    /// Profile selected on the command line or in `xeval.toml`, otherwise
+    /// the global default profile.
+    pub fn profile(cli: &Cli, global: &Global) -> Result<String, ConfigError> {
+        Ok(cli
+            .profile()?
+            .unwrap_or_else(|| global.auth.default_profile().to_string()))
+    }
+
+    /// API settings from the auth profile, overridden by `xeval.toml` and
+    /// then by the environment. Retry settings for the running command and
+    /// `--max-retries` win over the general ones.
+    pub fn openai_config(cli: &Cli, global: &Global, profile: &str) -> OpenAiConfig {
+        let mut config = OpenAiConfig::from_profile(global, profile);
+        if let Ok(project) = cli.project() {
+            let openai = project.config.openai;
+            let mut retry = Self::retry_config(&openai.retry);
+            if let Some(command) = cli
+                .command
+                .as_ref()
+                .and_then(|command| openai.retry.commands.get(command.name()))
+            {
+                retry = retry.merge(Self::retry_config(command));
+            }
+            config = config.merge(OpenAiConfig {
+                base_url: openai.base_url,
+                headers: openai.headers,
+                connect_timeout: openai.connect_timeout,
+                read_timeout: openai.read_timeout,
+                proxy: openai.proxy,
+                // Relative to the project rather than the working directory
+                ca_bundle: openai.ca_bundle.map(|path| project.path.join(path)),
+                user_agent: openai.user_agent,
+                retry,
+            });
+        }
+        config.retry.max_retries = cli.max_retries.or(config.retry.max_retries);
+        config.with_env()
+    }
+
+    fn retry_config(retry: &ConfigOpenAiRetry) -> OpenAiRetryConfig {
+        OpenAiRetryConfig {
+            max_retries: retry.max_retries,
+            initial_backoff_ms: retry.initial_backoff_ms,
+            max_backoff_ms: retry.max_backoff_ms,
+            idempotency_keys: retry.idempotency_keys,
+        }
+    }
+
+    /// OpenAI project to send with requests: the project configured in
+    /// `xeval.toml`, then the default project of the auth profile.
+    pub fn project_header(&self, project: &Project) -> Option<String> {
+        project
+            .config
+            .openai
+            .project
+            .clone()
+            .or_else(|| self.openai.auth.project().map(String::from))
+    }
 }
@@ -9,6 +9,20 @@ pub struct Cli {
     #[arg(short, long, value_name = "FILE")]
     pub config: Option<PathBuf>,
 
+    /// OpenAI API token. Takes precedence over XEVAL_OPENAI_API_KEY,
+    /// OPENAI_API_KEY and the stored token.
+    #[arg(long, global = true, value_name = "TOKEN")]
+    pub token: Option<String>,
+
+    /// Auth profile to use. Defaults to the project or global default profile.
+    #[arg(long, global = true, env = "XEVAL_PROFILE", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// How many times to retry rate limited or failed OpenAI requests,
+    /// 0 disables retrying. Overrides the retry settings in xeval.toml.
+    #[arg(long, global = true, env = "XEVAL_MAX_RETRIES", value_name = "N")]
+    pub max_retries: Option<u32>,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 }
@@ -16,7 +30,41 @@ pub struct Cli {
 impl Cli {
     pub async fn run() -> Result<()> {
         let cli = Self::parse();
-        println!("CLI config={:?}", cli.config);
         Command::run(&cli).await
     }
+
+    /// Profile selected with `--profile`, `XEVAL_PROFILE` or in `xeval.toml`.
+    /// `None` falls back to the global default profile.
+    pub fn profile(&self) -> Result<Option<String>, ConfigError> {
+        if let Some(profile) = &self.profile {
+            return Ok(Some(profile.clone()));
+        }
+        match Config::find(&self.config) {
+            Ok(config) => Ok(config.profile),
+            // Outside of a project the global default applies
+            Err(ConfigError::NotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Resolve the project using `--config` (file or dir), falling back to
+    /// the current directory.
+    pub fn project(&self) -> Result<Project> {
+        let path = match &self.config {
+            Some(p) if p.is_file() => p
+                .parent()
+                .map(|p| p.to_path_buf())
+                .unwrap_or_else(|| PathBuf::from(".")),
+            Some(p) if p.is_dir() => p.clone(),
+            _ => std::env::current_dir().context("Failed to get current directory")?,
+        };
+        // Load config (honor --config strictly)
+        let config = Config::find(&self.config)?;
+        let config_path = self.config.clone().filter(|p| p.is_file());
+        Ok(Project {
+            path,
+            config,
+            config_path,
+        })
+    }
 }
@@ -0,0 +1,221 @@
+use crate::prelude::*;
+
+#[derive(clap::Args)]
+pub struct AuthArgs {
+    #[command(subcommand)]
+    command: AuthCommand,
+}
+
+#[derive(clap::Subcommand)]
+pub enum AuthCommand {
+    /// Verify and store an OpenAI API token. Uses --token when given.
+    Login,
+
+    /// Remove the stored OpenAI API token of the profile.
+    Logout,
+
+    /// Show the active token, its organization and reachable projects.
+    Status,
+
+    /// Check that the active token is accepted by OpenAI.
+    Verify,
+
+    /// List stored auth profiles.
+    List,
+
+    /// Make a profile the global default.
+    Use {
+        /// Name of the profile.
+        profile: String,
+    },
+
+    /// Remove a stored profile.
+    Remove {
+        /// Name of the profile.
+        profile: String,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum AuthCmdError {
+    #[error("Auth profile '{0}' not found, run `xeval auth list` to see stored profiles")]
+    UnknownProfile(String),
+
+    #[error("No OpenAI API token found for profile '{0}', run `xeval auth login`")]
+    NotLoggedIn(String),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    Global(#[from] GlobalError),
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
+    #[error(transparent)]
+    OpenAi(#[from] OpenAiError),
+
+    #[error(transparent)]
+    OpenAiAuth(#[from] OpenAiAuthError),
+
+    #[error(transparent)]
+    Anyhow(#[from] anyhow::Error),
+}
+
+pub struct AuthCmd {}
+
+impl AuthCmd {
+    pub async fn run<'a>(cli: &'a Cli, args: &'a AuthArgs) -> Result<(), AuthCmdError> {
+        let mut global = Global::resolve()?;
+
+        match &args.command {
+            AuthCommand::Login => Self::login(cli, &mut global).await,
+
+            AuthCommand::Logout => {
+                let profile = Auth::profile(cli, &global)?;
+                OpenAiAuth::erase(&mut global, &profile)?;
+                UiMessage::success(&format!("Logged out of profile '{profile}'"));
+                Ok(())
+            }
+
+            AuthCommand::Status => Self::status(cli, &global).await,
+
+            AuthCommand::Verify => {
+                let openai = Self::detect(cli, &global)?;
+                openai.check().await?;
+                UiMessage::success(&format!(
+                    "OpenAI API token from {} is valid",
+                    openai.auth.source()
+                ));
+                Ok(())
+            }
+
+            AuthCommand::List => Self::list(cli, &global),
+
+            AuthCommand::Use { profile } => {
+                if !global.auth.has_profile(profile) {
+                    return Err(AuthCmdError::UnknownProfile(profile.clone()));
+                }
+                global.auth.set_default_profile(profile);
+                global.persist_auth()?;
+                UiMessage::success(&format!("Using auth profile '{profile}' by default"));
+                Ok(())
+            }
+
+            AuthCommand::Remove { profile } => {
+                if !global.auth.remove_profile(profile) {
+                    return Err(AuthCmdError::UnknownProfile(profile.clone()));
+                }
+                global.persist_auth()?;
+                UiMessage::success(&format!("Removed auth profile '{profile}'"));
+                Ok(())
+            }
+        }
+    }
+
+    async fn login(cli: &Cli, global: &mut Global) -> Result<(), AuthCmdError> {
+        let profile = Auth::profile(cli, global)?;
+        let auth = match &cli.token {
+            Some(token) => OpenAiAuth::new(token.as_str().into(), OpenAiAuthSource::Flag),
+            None => {
+                if !Auth::interactive() {
+                    return Err(AuthError::NonInteractive.into());
+                }
+                let token = UiOpenAiToken::inquire_token()?
+                    .ok_or_else(|| anyhow::anyhow!("No token provided"))?;
+                OpenAiAuth::new(token, OpenAiAuthSource::Prompt)
+            }
+        };
+
+        let spinner = UiTheme::start_spinner("Verifying OpenAI API token");
+        let openai = OpenAi::unverified(
+            auth.with_profile(global, &profile),
+            Auth::openai_config(cli, global, &profile),
+        )?;
+        let verified = openai.check().await;
+        spinner.finish_and_clear();
+        verified?;
+
+        openai.auth.persist(global)?;
+        UiMessage::success(&format!(
+            "OpenAI API token verified & saved to profile '{profile}'"
+        ));
+        Ok(())
+    }
+
+    async fn status(cli: &Cli, global: &Global) -> Result<(), AuthCmdError> {
+        let openai = Self::detect(cli, global)?;
+        let auth = &openai.auth;
+
+        let field = |name: &str, value: &str| println!("{:<14}{}", style(name).dim(), value);
+        field("Profile", auth.profile());
+        field("Source", &auth.source().to_string());
+        field("Token", &auth.masked_token());
+        field("Organization", auth.organization().unwrap_or("default"));
+        field("Project", auth.project().unwrap_or("default"));
+        field("Base URL", openai.config.base_url());
+
+        let spinner = UiTheme::start_spinner("Loading OpenAI projects");
+        let projects = openai.list_all_projects(Default::default()).await;
+        spinner.finish_and_clear();
+        match projects {
+            Ok(projects) => {
+                println!("{}", style("Projects").dim());
+                for project in projects {
+                    println!("  {} {}", project.name, style(&project.id).dim());
+                }
+            }
+            // Listing projects needs an admin key
+            Err(err) => UiMessage::warn(&format!("Can't list projects: {err}")),
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the active token without prompting.
+    fn detect(cli: &Cli, global: &Global) -> Result<OpenAi, AuthCmdError> {
+        let profile = Auth::profile(cli, global)?;
+        let token = cli.token.clone().map(OpenAiToken::from);
+        let config = Auth::openai_config(cli, global, &profile);
+        let openai = OpenAi::detect(global, Some(&profile), token, config)?
+            .ok_or(AuthCmdError::NotLoggedIn(profile))?;
+        Ok(openai)
+    }
+
+    fn list(cli: &Cli, global: &Global) -> Result<(), AuthCmdError> {
+        let profiles = global.auth.profiles();
+        if profiles.is_empty() {
+            UiMessage::info("No auth profiles stored, run `xeval auth login` to add one");
+            return Ok(());
+        }
+
+        let active = Auth::profile(cli, global)?;
+        for name in profiles {
+            let marker = if name == active { "*" } else { " " };
+            let details = match OpenAiAuth::stored(global, &name) {
+                Some(auth) => [
+                    Some(auth.masked_token()),
+                    auth.organization().map(String::from),
+                    auth.project().map(String::from),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" "),
+                None => match OpenAiAuth::credential_helper(global, &name) {
+                    Some(helper) => format!("credential helper `{}`", helper.command()),
+                    None => "no OpenAI token".to_string(),
+                },
+            };
+            println!(
+                "{} {} {}",
+                style(marker).green(),
+                style(&name).bold(),
+                style(details).dim()
+            );
+        }
+
+        Ok(())
+    }
+}
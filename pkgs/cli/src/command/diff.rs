@@ -0,0 +1,66 @@
+use crate::prelude::*;
+
+#[derive(clap::Args)]
+pub struct DiffArgs {
+    /// Name of the eval spec to diff. Diffs all specs when omitted.
+    eval: Option<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum DiffError {
+    #[error(transparent)]
+    Global(#[from] GlobalError),
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
+    #[error(transparent)]
+    Lock(#[from] LockError),
+
+    #[error(transparent)]
+    Anyhow(#[from] anyhow::Error),
+}
+
+pub struct DiffCmd {}
+
+impl DiffCmd {
+    pub async fn run<'a>(cli: &'a Cli, args: &'a DiffArgs) -> Result<(), DiffError> {
+        let project = cli.project()?;
+        let mut specs = xeval_files::find_eval_specs(&project.config.evals, &project.path)?;
+        if let Some(name) = &args.eval {
+            specs.retain(|(_, spec)| &spec.name == name);
+            if specs.is_empty() {
+                return Err(anyhow::anyhow!("No eval spec named '{name}' found").into());
+            }
+        }
+
+        let mut global = Global::resolve()?;
+        let auth = Auth::ensure(cli, &mut global, AuthState::Existing).await?;
+        let project_header = auth.project_header(&project);
+        let state = OpenAiLocalProjectState::new(&project, project_header.as_deref())?;
+
+        let lock = Lock::read(&project)?;
+        let spinner = UiTheme::start_spinner("Syncing OpenAI evals");
+        let by_name = OpenAiLocalEvals::resolve(
+            &auth,
+            &state,
+            true,
+            project_header.clone(),
+            &lock,
+            Lock::project_key(project_header.as_deref()),
+        )
+        .await;
+        spinner.finish_and_clear();
+        let by_name = by_name?;
+
+        for (_, spec) in specs {
+            let local = spec.to_openai_eval()?;
+            match by_name.get(&spec.name) {
+                Some(remote) => UiDiff::print(&spec.name, &OpenAiEvalDiff::new(remote, &local)),
+                None => println!("{} {} is not on OpenAI yet", style("+").green(), spec.name),
+            }
+        }
+
+        Ok(())
+    }
+}
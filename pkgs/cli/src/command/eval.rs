@@ -1,14 +1,18 @@
 use crate::prelude::*;
 use serde_json::Value;
-use serde_json::json;
 use sha2::{Digest, Sha256};
 use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 
 #[derive(clap::Args)]
 pub struct EvalArgs {
     /// Watch for changes.
     #[arg(short, long, default_value_t = false)]
     watch: bool,
+
+    /// Print what a sync would change without writing anything.
+    #[arg(long, default_value_t = false, conflicts_with = "watch")]
+    dry_run: bool,
 }
 
 #[derive(Error, Debug)]
@@ -19,9 +23,15 @@ pub enum EvalError {
     #[error(transparent)]
     Global(#[from] GlobalError),
 
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
     #[error(transparent)]
     OpenAiEvals(#[from] OpenAiEvalsError),
 
+    #[error(transparent)]
+    Lock(#[from] LockError),
+
     #[error(transparent)]
     Anyhow(#[from] anyhow::Error),
 }
@@ -30,148 +40,373 @@ pub struct EvalCmd {}
 
 impl EvalCmd {
     pub async fn run<'a>(cli: &'a Cli, args: &'a EvalArgs) -> Result<(), EvalError> {
-        // Determine base path from --config (file or dir); fallback to CWD
-        let base_path = match &cli.config {
-            Some(p) if p.is_file() => p
-                .parent()
-                .map(|p| p.to_path_buf())
-                .unwrap_or_else(|| PathBuf::from(".")),
-            Some(p) if p.is_dir() => p.clone(),
-            _ => std::env::current_dir().context("Failed to get current directory")?,
-        };
-        let project = Project {
-            path: base_path.clone(),
-        };
-        // Load config (honor --config strictly)
-        let config = Config::find(&cli.config)?;
-        let evals_glob = config.evals.clone();
+        let project = cli.project()?;
+        let evals_glob = project.config.evals.clone();
 
         // Load local YAML specs
-        let specs = xeval_files::find_eval_specs(&evals_glob, &project.path)?;
-        if specs.is_empty() {
-            UiMessage::warn(&format!(
-                "No eval YAML files found for glob '{}' in {}",
-                evals_glob,
-                project.path.display()
-            ));
-            return Ok(());
+        let specs = if args.watch {
+            Vec::new()
         } else {
-            UiMessage::info(&format!(
-                "Found {} eval file(s) for glob '{}'",
-                specs.len(),
-                evals_glob
-            ));
-        }
+            let specs = xeval_files::find_eval_specs(&evals_glob, &project.path)?;
+            if specs.is_empty() {
+                UiMessage::warn(&format!(
+                    "No eval YAML files found for glob '{}' in {}",
+                    evals_glob,
+                    project.path.display()
+                ));
+                return Ok(());
+            } else {
+                UiMessage::info(&format!(
+                    "Found {} eval file(s) for glob '{}'",
+                    specs.len(),
+                    evals_glob
+                ));
+            }
+            specs
+        };
 
         // Auth and fetch remote evals after we know we have local work
         let mut global = Global::resolve()?;
         let spinner = UiTheme::start_spinner("Logging in with OpenAI API...");
-        let auth = Auth::ensure(&mut global, AuthState::Existing).await?;
+        let auth = Auth::ensure(cli, &mut global, AuthState::Existing).await?;
 
-        let state = OpenAiLocalProjectState::new(&project)?;
+        let project_header = auth.project_header(&project);
+        let state = OpenAiLocalProjectState::new(&project, project_header.as_deref())?;
+        let lock = Lock::read(&project)?;
+        let lock_key = Lock::project_key(project_header.as_deref()).to_string();
 
-        let spinner = UiTheme::start_spinner("Syncing OpenAI evals");
-        let remote_evals = OpenAiLocalEvals::sync(&auth, &state, false, None).await?;
+        spinner.set_message("Syncing OpenAI evals");
+        // When every spec is locked, fetch just those evals instead of listing all.
+        // Watch mode may pick up new specs, so it always needs the full list.
+        let names: Vec<String> = specs.iter().map(|(_, spec)| spec.name.clone()).collect();
+        let locked = if args.watch {
+            None
+        } else {
+            OpenAiLocalEvals::fetch_locked(
+                &auth,
+                project_header.as_deref(),
+                &lock,
+                &lock_key,
+                &names,
+            )
+            .await?
+        };
+        let by_name = match locked {
+            Some(by_name) => by_name,
+            None => {
+                // A plan is only useful against the current remote state
+                OpenAiLocalEvals::resolve(
+                    &auth,
+                    &state,
+                    args.dry_run,
+                    project_header.clone(),
+                    &lock,
+                    &lock_key,
+                )
+                .await?
+            }
+        };
         spinner.finish_and_clear();
 
-        // Index remote evals by xeval_name metadata (prefer the latest by created_at)
-        let mut by_name: HashMap<String, OpenAiEval> = HashMap::new();
-        for eval in remote_evals {
-            if let Some(md) = &eval.metadata {
-                if let Some(name) = md.get("xeval_name") {
-                    match by_name.get(name) {
-                        Some(existing) => {
-                            if eval.created_at > existing.created_at {
-                                by_name.insert(name.clone(), eval);
-                            }
-                        }
-                        None => {
-                            by_name.insert(name.clone(), eval);
-                        }
-                    }
+        let mut sync = EvalSync {
+            project: &project,
+            auth: &auth,
+            by_name,
+            lock,
+            lock_key,
+            project_header,
+        };
+
+        if args.watch {
+            return EvalWatch::new(&project)?.run(&mut sync).await;
+        }
+
+        if args.dry_run {
+            let plans = specs
+                .iter()
+                .map(|(_, spec)| Ok(sync.plan(&spec.name, &spec.to_openai_eval()?)))
+                .collect::<Result<Vec<_>>>()?;
+            UiPlan::print(&plans);
+            return Ok(());
+        }
+
+        for (path, spec) in specs {
+            match sync.spec(&path, &spec.name, spec.to_openai_eval()?).await? {
+                EvalSyncOutcome::UpToDate => UiMessage::info(&format!("Up-to-date: {}", spec.name)),
+                EvalSyncOutcome::Updated => {
+                    UiMessage::success(&format!("Updated eval: {}", spec.name))
+                }
+                EvalSyncOutcome::Created => {
+                    UiMessage::success(&format!("Created eval: {}", spec.name))
                 }
             }
         }
 
-        for (_path, spec) in specs {
-            let mut local = spec.to_openai_eval()?;
-            let local_hash = content_hash(&local);
-            let mut md: BTreeMap<String, String> = local.metadata.take().unwrap_or_default();
-            md.insert("xeval_name".into(), spec.name.clone());
-            md.insert("xeval_hash".into(), local_hash.clone());
-            local.metadata = Some(md.clone());
-
-            if let Some(remote) = by_name.get(&spec.name) {
-                let remote_hash = content_hash(remote);
-                if remote_hash == local_hash {
-                    let needs_metadata_update = remote
-                        .metadata
-                        .as_ref()
-                        .and_then(|m| m.get("xeval_hash").cloned())
-                        .unwrap_or_default()
-                        != local_hash;
-
-                    if needs_metadata_update {
-                        let _ = auth
-                            .openai
-                            .update_eval_metadata(
-                                project_header.as_deref(),
-                                &remote.id,
-                                Some(&spec.name),
-                                Some(&md),
-                            )
-                            .await?;
-                    }
-                    UiMessage::info(&format!("Up-to-date: {}", spec.name));
-                } else {
-                    // Create a new eval (OpenAI API does not allow changing core fields)
-                    let spinner =
-                        UiTheme::start_spinner(&format!("Updating OpenAI eval: {}", spec.name));
-                    let upsert = OpenAiEvalUpsert {
-                        name: spec.name.clone(),
-                        metadata: Some(md),
-                        data_source_config: map_upsert_config(&local.data_source_config),
-                        testing_criteria: local.testing_criteria.clone(),
-                    };
-                    let _created = auth
-                        .openai
-                        .create_eval(project_header.as_deref(), &upsert)
-                        .await?;
-                    spinner.finish_and_clear();
-                    UiMessage::success(&format!("Updated eval: {}", spec.name));
-                }
-            } else {
-                // Missing: create
-                let upsert = OpenAiEvalUpsert {
-                    name: spec.name.clone(),
-                    metadata: Some(md),
-                    data_source_config: map_upsert_config(&local.data_source_config),
-                    testing_criteria: local.testing_criteria.clone(),
-                };
-                let _created = auth
+        Ok(())
+    }
+}
+
+//#region Sync
+
+pub enum EvalSyncOutcome {
+    UpToDate,
+    Updated,
+    Created,
+}
+
+/// Pushes local specs to OpenAI, tracking the current remote eval per name
+/// and recording it in the lock file.
+struct EvalSync<'a> {
+    project: &'a Project,
+    auth: &'a Auth,
+    by_name: HashMap<String, OpenAiEval>,
+    lock: Lock,
+    lock_key: String,
+    /// OpenAI project sent with every request, `None` for the default project.
+    project_header: Option<String>,
+}
+
+impl EvalSync<'_> {
+    /// Match a spec's local eval against its remote eval without writing
+    /// anything.
+    fn plan(&self, name: &str, local: &OpenAiEval) -> OpenAiEvalPlan {
+        OpenAiEvalPlan::new(name, self.by_name.get(name), local, &content_hash(local))
+    }
+
+    /// Sync the eval converted from the spec at `path`.
+    async fn spec(
+        &mut self,
+        path: &Path,
+        name: &str,
+        local: OpenAiEval,
+    ) -> Result<EvalSyncOutcome> {
+        let local_hash = content_hash(&local);
+        let outcome = self.push(name, local, &local_hash).await?;
+        self.lock(path, name, &local_hash)?;
+        Ok(outcome)
+    }
+
+    /// Point the lock entry at the current remote eval, writing it on change.
+    fn lock(&mut self, path: &Path, name: &str, local_hash: &str) -> Result<()> {
+        let Some(remote) = self.by_name.get(name) else {
+            return Ok(());
+        };
+        let changed = self.lock.record(
+            name,
+            &relative_path(&self.project.path, path),
+            local_hash,
+            &self.lock_key,
+            LockRemote {
+                id: remote.id.clone(),
+                hash: content_hash(remote),
+            },
+        );
+        if changed {
+            self.lock.write(self.project)?;
+        }
+        Ok(())
+    }
+
+    async fn push(
+        &mut self,
+        name: &str,
+        mut local: OpenAiEval,
+        local_hash: &str,
+    ) -> Result<EvalSyncOutcome> {
+        let plan = self.plan(name, &local);
+
+        let mut md: BTreeMap<String, String> = local.metadata.take().unwrap_or_default();
+        md.insert("xeval_name".into(), name.to_string());
+        md.insert("xeval_hash".into(), local_hash.to_string());
+        local.metadata = Some(md.clone());
+
+        let outcome = match (plan.action, self.by_name.get(name)) {
+            (OpenAiEvalPlanAction::UpdateMetadata, Some(remote)) => {
+                // Keep lineage and other remote keys, only refresh ours
+                let mut remote_md = remote.metadata.clone().unwrap_or_default();
+                remote_md.extend(md);
+                let _ = self
+                    .auth
                     .openai
-                    .create_eval(project_header.as_deref(), &upsert)
+                    .update_eval_metadata(
+                        self.project_header.as_deref(),
+                        &remote.id,
+                        Some(name),
+                        Some(&remote_md),
+                    )
                     .await?;
-                UiMessage::success(&format!("Created eval: {}", spec.name));
+                return Ok(EvalSyncOutcome::UpToDate);
+            }
+            (OpenAiEvalPlanAction::Unchanged, _) => return Ok(EvalSyncOutcome::UpToDate),
+            // Create a new eval (OpenAI API does not allow changing core fields)
+            (OpenAiEvalPlanAction::Replace(_), _) => EvalSyncOutcome::Updated,
+            // Missing: create
+            _ => EvalSyncOutcome::Created,
+        };
+
+        let commit = git_commit(&self.project.path);
+        OpenAiEvalLineage::stamp(&mut md, self.by_name.get(name), commit.as_deref());
+
+        let spinner = UiTheme::start_spinner(&format!("Syncing OpenAI eval: {name}"));
+        let upsert = OpenAiEvalUpsert {
+            name: name.to_string(),
+            metadata: Some(md),
+            data_source_config: map_upsert_config(&local.data_source_config),
+            testing_criteria: local.testing_criteria.clone(),
+        };
+        let created = self
+            .auth
+            .openai
+            .create_eval(self.project_header.as_deref(), &upsert)
+            .await;
+        spinner.finish_and_clear();
+
+        self.by_name.insert(name.to_string(), created?);
+        Ok(outcome)
+    }
+}
+
+//#endregion
+
+//#region Watch
+
+/// Re-syncs specs whenever they, their datasets or grader sources change.
+struct EvalWatch<'a> {
+    project: &'a Project,
+    watcher: FileWatcher,
+    /// Local content hash of the last successfully synced version of a spec.
+    hashes: HashMap<PathBuf, String>,
+    /// Files a spec depends on besides itself.
+    references: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+impl<'a> EvalWatch<'a> {
+    fn new(project: &'a Project) -> Result<Self> {
+        Ok(Self {
+            project,
+            watcher: FileWatcher::new(&project.path)?,
+            hashes: HashMap::new(),
+            references: HashMap::new(),
+        })
+    }
+
+    async fn run(mut self, sync: &mut EvalSync<'_>) -> Result<(), EvalError> {
+        let evals_glob = &self.project.config.evals;
+        let mut paths = self.spec_paths()?;
+        if paths.is_empty() {
+            UiMessage::warn(&format!(
+                "No eval YAML files found yet for glob '{}' in {}",
+                evals_glob,
+                self.project.path.display()
+            ));
+        }
+        for path in &paths {
+            self.check(sync, path).await;
+        }
+        UiMessage::info(&format!(
+            "Watching '{evals_glob}' for changes (Ctrl+C to stop)"
+        ));
+
+        loop {
+            let changed = self.watcher.next_changes().await?;
+            let current = self.spec_paths()?;
+
+            for path in paths.iter().filter(|p| !current.contains(p)) {
+                UiMessage::warn(&format!("Removed: {}", self.display(path)));
+                self.hashes.remove(path);
+                self.references.remove(path);
             }
+
+            for path in &current {
+                let affected = changed.contains(path)
+                    || self
+                        .references
+                        .get(path)
+                        .is_some_and(|refs| refs.iter().any(|r| changed.contains(r)));
+                if affected || !paths.contains(path) {
+                    self.check(sync, path).await;
+                }
+            }
+
+            paths = current;
+        }
+    }
+
+    fn spec_paths(&self) -> Result<Vec<PathBuf>> {
+        let paths =
+            xeval_files::find_eval_spec_paths(&self.project.config.evals, &self.project.path)?;
+        Ok(paths.iter().map(|p| normalize_path(p)).collect())
+    }
+
+    /// Validate and sync one spec, reporting errors without stopping the watch.
+    async fn check(&mut self, sync: &mut EvalSync<'_>, path: &Path) {
+        if let Err(err) = self.try_check(sync, path).await {
+            UiMessage::error(err.context(format!("Failed to sync {}", self.display(path))));
+        }
+    }
+
+    async fn try_check(&mut self, sync: &mut EvalSync<'_>, path: &Path) -> Result<()> {
+        let spec = xeval_files::parse_eval_spec_from_path(path)?;
+
+        let references: Vec<PathBuf> = xeval_files::referenced_paths(path, &spec)
+            .iter()
+            .map(|p| normalize_path(p))
+            .collect();
+        for reference in &references {
+            self.watcher.add(reference)?;
         }
+        self.references.insert(path.to_path_buf(), references);
 
+        let rows = xeval_files::load_eval_dataset(path, &spec)?;
+        let rows = rows
+            .map(|r| format!(", {} row(s)", r.len()))
+            .unwrap_or_default();
+
+        let local = spec.to_openai_eval()?;
+        let hash = content_hash(&local);
+        if self.hashes.get(path) == Some(&hash) {
+            UiMessage::info(&format!("Unchanged: {}{}", self.display(path), rows));
+            return Ok(());
+        }
+
+        let status = match sync.spec(path, &spec.name, local).await? {
+            EvalSyncOutcome::UpToDate => "Up-to-date",
+            EvalSyncOutcome::Updated => "Updated",
+            EvalSyncOutcome::Created => "Created",
+        };
+        UiMessage::success(&format!(
+            "{status}: {} ({}){rows}",
+            spec.name,
+            self.display(path)
+        ));
+        self.hashes.insert(path.to_path_buf(), hash);
         Ok(())
     }
+
+    fn display(&self, path: &Path) -> String {
+        let root = normalize_path(&self.project.path);
+        path.strip_prefix(&root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
 }
 
-fn content_hash(eval: &OpenAiEval) -> String {
-    // Hash only content fields that matter for identity
-    let v = json!({
-        "name": eval.name,
-        "data_source_config": eval.data_source_config,
-        "testing_criteria": eval.testing_criteria,
-    });
+//#endregion
+
+pub fn content_hash(eval: &OpenAiEval) -> String {
+    let v = content_value(eval);
     let mut hasher = Sha256::new();
     hasher.update(serde_json::to_vec(&v).unwrap());
     format!("{:x}", hasher.finalize())
 }
 
+/// Normalized content fields that matter for identity, shared by hashing and
+/// diffing so local and remote evals compare semantically.
+pub fn content_value(eval: &OpenAiEval) -> Value {
+    eval.normalized()
+}
+
 fn map_upsert_config(cfg: &OpenAiDataSourceConfig) -> OpenAiDataSourceConfigUpsert {
     match cfg {
         OpenAiDataSourceConfig::Custom(c) => {
@@ -207,5 +442,9 @@ fn map_upsert_config(cfg: &OpenAiDataSourceConfig) -> OpenAiDataSourceConfigUpse
                 },
             )
         }
+
+        OpenAiDataSourceConfig::Unknown(value) => {
+            OpenAiDataSourceConfigUpsert::Unknown(value.clone())
+        }
     }
 }
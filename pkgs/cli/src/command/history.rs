@@ -0,0 +1,77 @@
+use crate::prelude::*;
+use chrono::DateTime;
+
+#[derive(clap::Args)]
+pub struct HistoryArgs {
+    /// Name of the eval spec to show versions of.
+    eval: String,
+}
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error(transparent)]
+    Global(#[from] GlobalError),
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
+    #[error(transparent)]
+    Anyhow(#[from] anyhow::Error),
+}
+
+pub struct HistoryCmd {}
+
+impl HistoryCmd {
+    pub async fn run<'a>(cli: &'a Cli, args: &'a HistoryArgs) -> Result<(), HistoryError> {
+        let project = cli.project()?;
+
+        let mut global = Global::resolve()?;
+        let auth = Auth::ensure(cli, &mut global, AuthState::Existing).await?;
+        let project_header = auth.project_header(&project);
+        let state = OpenAiLocalProjectState::new(&project, project_header.as_deref())?;
+
+        let spinner = UiTheme::start_spinner("Syncing OpenAI evals");
+        let remote_evals =
+            OpenAiLocalEvals::sync(&auth, &state, true, project_header.clone()).await?;
+        spinner.finish_and_clear();
+
+        let chain = OpenAiEvalLineage::chain(remote_evals, &args.eval);
+        if chain.is_empty() {
+            UiMessage::warn(&format!("No OpenAI evals found for '{}'", args.eval));
+            return Ok(());
+        }
+
+        let mut previous: Option<&OpenAiEval> = None;
+        for eval in &chain {
+            let created_at = DateTime::from_timestamp(eval.created_at, 0)
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .unwrap_or_else(|| eval.created_at.to_string());
+            let commit = OpenAiEvalLineage::commit(eval)
+                .map(|c| format!(" @ {}", c.chars().take(10).collect::<String>()))
+                .unwrap_or_default();
+            println!(
+                "{} {} {}{}",
+                style(format!("v{}", OpenAiEvalLineage::version(eval))).bold(),
+                eval.id,
+                style(created_at).dim(),
+                style(commit).dim()
+            );
+
+            // Older evals may predate lineage metadata, fall back to the prior entry
+            let parent = OpenAiEvalLineage::previous(eval)
+                .and_then(|id| chain.iter().find(|e| e.id == id))
+                .or(previous);
+            if let Some(parent) = parent {
+                let changes = json_diff(&content_value(parent), &content_value(eval));
+                if changes.is_empty() {
+                    println!("    {}", style("no content changes").dim());
+                } else {
+                    UiPlan::print_changes(&changes);
+                }
+            }
+            previous = Some(eval);
+        }
+
+        Ok(())
+    }
+}
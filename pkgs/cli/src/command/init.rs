@@ -11,6 +11,9 @@ pub enum InitError {
     #[error(transparent)]
     Global(#[from] GlobalError),
 
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
     #[error(transparent)]
     OpenAi(#[from] OpenAiError),
 
@@ -39,15 +42,22 @@ pub struct InitCmd {}
 impl InitCmd {
     pub async fn run<'a>(cli: &'a Cli, args: &'a InitArgs) -> Result<(), InitError> {
         let mut global = Global::resolve()?;
-        let _ = Auth::ensure(&mut global, AuthState::New).await?;
+        let auth = Auth::ensure(cli, &mut global, AuthState::New).await?;
 
         let evals_glob = UiConfig::inquire_evals_glob()?;
 
         let mut config = Config::init(&args.path, args.force)?;
         config.evals = evals_glob;
 
-        let config_path = cli.config.clone();
-        let project = Project {};
+        // Listing projects needs an admin key, keep the default project if it fails
+        match OpenAiLocalProject::list(&auth).await {
+            Ok(projects) => {
+                config.openai.project = UiOpenAiProject::inquire_project(&projects, None)?
+            }
+            Err(err) => UiMessage::warn(&format!(
+                "{err:#}. Syncing into the default project, run `xeval project` to pick one later"
+            )),
+        }
 
         Config::write_new(&args.path, args.force, &config)?;
 
@@ -1,11 +1,32 @@
 use crate::prelude::*;
 
+mod auth;
+pub use auth::*;
+
+mod diff;
+pub use diff::*;
+
 mod eval;
 pub use eval::*;
 
+mod history;
+pub use history::*;
+
 mod init;
 pub use init::*;
 
+mod project;
+pub use project::*;
+
+mod prune;
+pub use prune::*;
+
+mod pull;
+pub use pull::*;
+
+mod run;
+pub use run::*;
+
 #[derive(clap::Subcommand)]
 pub enum Command {
     /// Initialize a new xeval project in an existing directory
@@ -13,15 +34,65 @@ pub enum Command {
 
     /// Run all evals in the project.
     Eval(EvalArgs),
+
+    /// Run an eval against its dataset on OpenAI.
+    Run(RunArgs),
+
+    /// Delete orphaned and superseded evals on OpenAI.
+    Prune(PruneArgs),
+
+    /// Show the version history of an eval on OpenAI.
+    History(HistoryArgs),
+
+    /// Show how local specs differ from their evals on OpenAI.
+    Diff(DiffArgs),
+
+    /// Import evals from OpenAI as YAML specs.
+    Pull(PullArgs),
+
+    /// Choose the OpenAI project to sync evals into.
+    Project(ProjectArgs),
+
+    /// Manage OpenAI auth profiles.
+    Auth(AuthArgs),
 }
 
 impl Command {
+    /// Name used to look up per-command settings in `xeval.toml`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Init(_) => "init",
+            Command::Eval(_) => "eval",
+            Command::Run(_) => "run",
+            Command::Prune(_) => "prune",
+            Command::History(_) => "history",
+            Command::Diff(_) => "diff",
+            Command::Pull(_) => "pull",
+            Command::Project(_) => "project",
+            Command::Auth(_) => "auth",
+        }
+    }
+
     pub async fn run(cli: &Cli) -> Result<()> {
         match &cli.command {
             Some(Command::Init(args)) => Ok(InitCmd::run(cli, args).await?),
 
             Some(Command::Eval(args)) => Ok(EvalCmd::run(cli, args).await?),
 
+            Some(Command::Run(args)) => Ok(RunCmd::run(cli, args).await?),
+
+            Some(Command::Prune(args)) => Ok(PruneCmd::run(cli, args).await?),
+
+            Some(Command::History(args)) => Ok(HistoryCmd::run(cli, args).await?),
+
+            Some(Command::Diff(args)) => Ok(DiffCmd::run(cli, args).await?),
+
+            Some(Command::Pull(args)) => Ok(PullCmd::run(cli, args).await?),
+
+            Some(Command::Project(args)) => Ok(ProjectCmd::run(cli, args).await?),
+
+            Some(Command::Auth(args)) => Ok(AuthCmd::run(cli, args).await?),
+
             None => unreachable!("No command was provided"),
         }
     }
@@ -0,0 +1,68 @@
+use crate::prelude::*;
+
+#[derive(clap::Args)]
+pub struct ProjectArgs {
+    /// OpenAI project id to use. Shows a picker when omitted.
+    id: Option<String>,
+    /// Switch back to the default project.
+    #[arg(long, conflicts_with = "id", default_value_t = false)]
+    default: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum ProjectError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error("Failed to read input from terminal")]
+    Terminal(#[from] dialoguer::Error),
+
+    #[error(transparent)]
+    Global(#[from] GlobalError),
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
+    #[error(transparent)]
+    Anyhow(#[from] anyhow::Error),
+}
+
+pub struct ProjectCmd {}
+
+impl ProjectCmd {
+    pub async fn run<'a>(cli: &'a Cli, args: &'a ProjectArgs) -> Result<(), ProjectError> {
+        let mut project = cli.project()?;
+        let current = project.config.openai.project.clone();
+
+        let selected = if args.default {
+            None
+        } else if let Some(id) = &args.id {
+            Some(id.clone())
+        } else {
+            let mut global = Global::resolve()?;
+            let auth = Auth::ensure(cli, &mut global, AuthState::Existing).await?;
+            OpenAiLocalProject::select(&auth, current.as_deref()).await?
+        };
+
+        if selected == current {
+            UiMessage::info(&format!("Already using {}", Self::describe(&selected)));
+            return Ok(());
+        }
+
+        project.config.openai.project = selected;
+        project.config.write_openai_project(&project)?;
+        UiMessage::success(&format!(
+            "Switched to {}",
+            Self::describe(&project.config.openai.project)
+        ));
+
+        Ok(())
+    }
+
+    fn describe(project: &Option<String>) -> String {
+        match project {
+            Some(id) => format!("OpenAI project {id}"),
+            None => "the default OpenAI project".to_string(),
+        }
+    }
+}
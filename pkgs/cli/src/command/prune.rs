@@ -0,0 +1,249 @@
+use crate::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+
+/// Metadata key marking evals pruned with `--tag` instead of deleted.
+pub const PRUNED_METADATA_KEY: &str = "xeval_pruned";
+
+#[derive(clap::Args)]
+pub struct PruneArgs {
+    /// Number of latest versions to keep for each eval that still has a spec.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    keep: u32,
+    /// Tag pruned evals with `xeval_pruned` metadata instead of deleting them.
+    #[arg(long, default_value_t = false)]
+    tag: bool,
+    /// Skip the confirmation prompt.
+    #[arg(short, long, default_value_t = false)]
+    yes: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum PruneError {
+    #[error("Failed to read input from terminal")]
+    Terminal(#[from] dialoguer::Error),
+
+    #[error(transparent)]
+    Global(#[from] GlobalError),
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
+    #[error(transparent)]
+    OpenAiEvals(#[from] OpenAiEvalsError),
+
+    #[error(transparent)]
+    Lock(#[from] LockError),
+
+    #[error(transparent)]
+    Anyhow(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PruneReason {
+    /// No local spec claims the eval's `xeval_name`.
+    Orphaned,
+    /// A newer version of a claimed eval exists.
+    Superseded,
+}
+
+pub struct PruneCandidate {
+    pub eval: OpenAiEval,
+    pub reason: PruneReason,
+}
+
+pub struct PruneCmd {}
+
+impl PruneCmd {
+    pub async fn run<'a>(cli: &'a Cli, args: &'a PruneArgs) -> Result<(), PruneError> {
+        let project = cli.project()?;
+        let specs = xeval_files::find_eval_specs(&project.config.evals, &project.path)?;
+        let claimed: HashSet<String> = specs.into_iter().map(|(_, spec)| spec.name).collect();
+
+        let mut global = Global::resolve()?;
+        let auth = Auth::ensure(cli, &mut global, AuthState::Existing).await?;
+        let project_header = auth.project_header(&project);
+        let state = OpenAiLocalProjectState::new(&project, project_header.as_deref())?;
+
+        let spinner = UiTheme::start_spinner("Syncing OpenAI evals");
+        let remote_evals =
+            OpenAiLocalEvals::sync(&auth, &state, true, project_header.clone()).await?;
+        spinner.finish_and_clear();
+
+        let lock = Lock::read(&project)?;
+        let locked: HashSet<&str> = lock
+            .evals
+            .values()
+            .flat_map(|entry| entry.remote.values().map(|remote| remote.id.as_str()))
+            .collect();
+
+        let candidates = Self::candidates(remote_evals, &claimed, &locked, args.keep as usize);
+        if candidates.is_empty() {
+            UiMessage::success("Nothing to prune");
+            return Ok(());
+        }
+
+        for candidate in &candidates {
+            let reason = match candidate.reason {
+                PruneReason::Orphaned => "no local spec",
+                PruneReason::Superseded => "superseded",
+            };
+            println!(
+                "{} {} {} ({})",
+                style("-").red(),
+                candidate.eval.name,
+                style(&candidate.eval.id).dim(),
+                reason
+            );
+        }
+
+        let action = if args.tag { "Tag" } else { "Delete" };
+        if !args.yes && !UiPrune::confirm(action, candidates.len())? {
+            UiMessage::warn("Prune canceled");
+            return Ok(());
+        }
+
+        let spinner = UiTheme::start_spinner(&format!("Pruning {} eval(s)", candidates.len()));
+        for candidate in &candidates {
+            let eval = &candidate.eval;
+            if args.tag {
+                let mut md = eval.metadata.clone().unwrap_or_default();
+                md.insert(PRUNED_METADATA_KEY.into(), "true".into());
+                auth.openai
+                    .update_eval_metadata(project_header.as_deref(), &eval.id, None, Some(&md))
+                    .await?;
+            } else {
+                auth.openai
+                    .delete_eval(project_header.as_deref(), &eval.id)
+                    .await?;
+            }
+        }
+        spinner.finish_and_clear();
+
+        // Refresh the cache so later syncs don't see pruned evals
+        OpenAiLocalEvals::sync(&auth, &state, true, project_header.clone()).await?;
+
+        let done = if args.tag { "Tagged" } else { "Deleted" };
+        UiMessage::success(&format!("{done} {} eval(s)", candidates.len()));
+        Ok(())
+    }
+
+    /// Pick every version of orphaned evals and all but the `keep` latest
+    /// versions of claimed ones. Evals the lock file points at, evals
+    /// already tagged as pruned and evals xeval didn't create are skipped.
+    pub fn candidates(
+        evals: Vec<OpenAiEval>,
+        claimed: &HashSet<String>,
+        locked: &HashSet<&str>,
+        keep: usize,
+    ) -> Vec<PruneCandidate> {
+        let mut by_name: BTreeMap<String, Vec<OpenAiEval>> = BTreeMap::new();
+        for eval in evals {
+            let Some(md) = &eval.metadata else {
+                continue;
+            };
+            let Some(name) = md.get("xeval_name").cloned() else {
+                continue;
+            };
+            if md.contains_key(PRUNED_METADATA_KEY) {
+                continue;
+            }
+            by_name.entry(name).or_default().push(eval);
+        }
+
+        let mut candidates = Vec::new();
+        for (name, mut versions) in by_name {
+            versions.sort_by_key(|eval| std::cmp::Reverse(eval.created_at));
+            let (reason, skip) = if claimed.contains(&name) {
+                (PruneReason::Superseded, keep)
+            } else {
+                (PruneReason::Orphaned, 0)
+            };
+            candidates.extend(
+                versions
+                    .into_iter()
+                    .skip(skip)
+                    .filter(|eval| !locked.contains(eval.id.as_str()))
+                    .map(|eval| PruneCandidate { eval, reason }),
+            );
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use xeval_openai::testing::{eval, named_eval};
+
+    fn pruned(
+        evals: Vec<OpenAiEval>,
+        claimed: &[&str],
+        locked: &[&str],
+        keep: usize,
+    ) -> Vec<String> {
+        let claimed = claimed.iter().map(|name| name.to_string()).collect();
+        let locked = locked.iter().copied().collect();
+        let mut ids: Vec<String> = PruneCmd::candidates(evals, &claimed, &locked, keep)
+            .into_iter()
+            .map(|candidate| candidate.eval.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn keeps_latest_versions_of_claimed_evals() {
+        let evals = vec![
+            named_eval("eval_1", 1, "math"),
+            named_eval("eval_3", 3, "math"),
+            named_eval("eval_2", 2, "math"),
+        ];
+        assert_eq!(
+            pruned(evals.clone(), &["math"], &[], 1),
+            vec!["eval_1", "eval_2"]
+        );
+        assert_eq!(pruned(evals, &["math"], &[], 2), vec!["eval_1"]);
+    }
+
+    #[test]
+    fn prunes_every_version_of_orphaned_evals() {
+        let evals = vec![
+            named_eval("eval_1", 1, "gone"),
+            named_eval("eval_2", 2, "gone"),
+        ];
+        assert_eq!(pruned(evals, &["math"], &[], 1), vec!["eval_1", "eval_2"]);
+    }
+
+    #[test]
+    fn keeps_locked_evals() {
+        let evals = vec![
+            named_eval("eval_1", 1, "math"),
+            named_eval("eval_2", 2, "math"),
+            named_eval("eval_3", 3, "gone"),
+        ];
+        assert_eq!(
+            pruned(evals, &["math"], &["eval_1", "eval_3"], 1),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn keeps_evals_without_xeval_metadata() {
+        let evals = vec![
+            eval("eval_1", json!({"created_at": 1})),
+            eval(
+                "eval_2",
+                json!({"created_at": 2, "metadata": {"owner": "someone"}}),
+            ),
+            eval(
+                "eval_3",
+                json!({
+                    "created_at": 3,
+                    "metadata": {"xeval_name": "gone", PRUNED_METADATA_KEY: "true"},
+                }),
+            ),
+        ];
+        assert!(pruned(evals, &[], &[], 1).is_empty());
+    }
+}
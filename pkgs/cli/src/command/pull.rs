@@ -0,0 +1,136 @@
+use crate::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use xeval_types::EvalSpec;
+use xeval_types::to_openai::slug_like;
+
+#[derive(clap::Args)]
+pub struct PullArgs {
+    /// Names of remote evals to pull. Pulls every eval without a local spec
+    /// when omitted.
+    evals: Vec<String>,
+    /// Directory to write specs to. Defaults to the base of the evals glob.
+    #[arg(short, long)]
+    dir: Option<PathBuf>,
+    /// Overwrite existing spec files.
+    #[arg(short, long, default_value_t = false)]
+    force: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum PullError {
+    #[error(transparent)]
+    Global(#[from] GlobalError),
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
+    #[error(transparent)]
+    Lock(#[from] LockError),
+
+    #[error(transparent)]
+    Anyhow(#[from] anyhow::Error),
+}
+
+pub struct PullCmd {}
+
+impl PullCmd {
+    pub async fn run<'a>(cli: &'a Cli, args: &'a PullArgs) -> Result<(), PullError> {
+        let project = cli.project()?;
+        let specs = xeval_files::find_eval_specs(&project.config.evals, &project.path)?;
+        let claimed: HashMap<String, PathBuf> = specs
+            .into_iter()
+            .map(|(path, spec)| (spec.name, path))
+            .collect();
+        let dir = args
+            .dir
+            .clone()
+            .unwrap_or_else(|| xeval_files::glob_base_dir(&project.config.evals, &project.path));
+
+        let mut global = Global::resolve()?;
+        let auth = Auth::ensure(cli, &mut global, AuthState::Existing).await?;
+        let project_header = auth.project_header(&project);
+        let state = OpenAiLocalProjectState::new(&project, project_header.as_deref())?;
+
+        let spinner = UiTheme::start_spinner("Syncing OpenAI evals");
+        let remote_evals =
+            OpenAiLocalEvals::sync(&auth, &state, true, project_header.clone()).await?;
+        spinner.finish_and_clear();
+
+        // Evals created in the dashboard have no `xeval_name`, use their name
+        let mut by_name: BTreeMap<String, OpenAiEval> =
+            OpenAiLocalEvals::latest_by(remote_evals, |eval| {
+                let md = eval.metadata.as_ref();
+                Some(
+                    md.and_then(|md| md.get("xeval_name"))
+                        .unwrap_or(&eval.name)
+                        .clone(),
+                )
+            })
+            .into_iter()
+            .collect();
+        if args.evals.is_empty() {
+            by_name.retain(|name, _| !claimed.contains_key(name));
+        } else {
+            for name in &args.evals {
+                if !by_name.contains_key(name) {
+                    UiMessage::warn(&format!("No OpenAI eval named '{name}' found"));
+                }
+            }
+            by_name.retain(|name, _| args.evals.contains(name));
+        }
+
+        if by_name.is_empty() {
+            UiMessage::info("Nothing to pull");
+            return Ok(());
+        }
+
+        let mut lock = Lock::read(&project)?;
+        for (name, eval) in by_name {
+            // Overwrite the existing spec in place rather than adding a second one
+            let path = match claimed.get(&name) {
+                Some(path) if args.force => path.clone(),
+                Some(_) => {
+                    UiMessage::warn(&format!(
+                        "Skipped {name}: it already has a local spec, pass --force to overwrite"
+                    ));
+                    continue;
+                }
+                None => dir.join(format!("{}.yaml", slug_like(&name))),
+            };
+            if path.exists() && !args.force {
+                UiMessage::warn(&format!(
+                    "Skipped {name}: {} already exists, pass --force to overwrite",
+                    path.display()
+                ));
+                continue;
+            }
+
+            let mut spec = match EvalSpec::from_openai_eval(&eval) {
+                Ok(spec) => spec,
+                Err(err) => {
+                    UiMessage::warn(&format!("Skipped {name}: {err:#}"));
+                    continue;
+                }
+            };
+            spec.name = name.clone();
+            xeval_files::write_eval_spec(&path, &spec)?;
+
+            // Pin the pulled eval so the next sync doesn't create a duplicate
+            let relative = relative_path(&project.path, &path);
+            lock.record(
+                &name,
+                &relative,
+                &content_hash(&spec.to_openai_eval()?),
+                Lock::project_key(project_header.as_deref()),
+                LockRemote {
+                    id: eval.id.clone(),
+                    hash: content_hash(&eval),
+                },
+            );
+            UiMessage::success(&format!("Pulled {name} to {relative}"));
+        }
+
+        lock.write(&project)?;
+        Ok(())
+    }
+}
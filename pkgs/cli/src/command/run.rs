@@ -0,0 +1,248 @@
+use crate::prelude::*;
+use serde_json::{Map, Value};
+use std::time::Duration;
+use xeval_types::to_openai::translate_template_placeholders;
+
+#[derive(clap::Args)]
+pub struct RunArgs {
+    /// Name of the eval spec to run.
+    eval: String,
+    /// Model to generate samples with, requires --prompt. Without it,
+    /// dataset rows must carry a precomputed `sample`.
+    #[arg(short, long, requires = "prompt")]
+    model: Option<String>,
+    /// Prompt template file used as the user message when generating samples.
+    #[arg(short, long, value_name = "FILE", requires = "model")]
+    prompt: Option<PathBuf>,
+    /// Seconds between run status checks.
+    #[arg(long, default_value_t = 2)]
+    poll_interval: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum RunError {
+    #[error(transparent)]
+    Global(#[from] GlobalError),
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
+    #[error(transparent)]
+    OpenAiEvalRuns(#[from] OpenAiEvalRunsError),
+
+    #[error(transparent)]
+    OpenAiFiles(#[from] OpenAiFilesError),
+
+    #[error(transparent)]
+    Lock(#[from] LockError),
+
+    #[error(transparent)]
+    Anyhow(#[from] anyhow::Error),
+}
+
+pub struct RunCmd {}
+
+impl RunCmd {
+    pub async fn run<'a>(cli: &'a Cli, args: &'a RunArgs) -> Result<(), RunError> {
+        let project = cli.project()?;
+        let specs = xeval_files::find_eval_specs(&project.config.evals, &project.path)?;
+        let (spec_path, spec) = specs
+            .into_iter()
+            .find(|(_, spec)| spec.name == args.eval)
+            .with_context(|| format!("No eval spec named '{}' found", args.eval))?;
+
+        let rows = xeval_files::load_eval_dataset(&spec_path, &spec)?.with_context(|| {
+            format!(
+                "Eval spec '{}' has no dataset, add `data` or `dataset` to {}",
+                spec.name,
+                spec_path.display()
+            )
+        })?;
+
+        // Clap makes --model and --prompt require each other
+        let prompt = match &args.prompt {
+            Some(path) => Some(
+                std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read prompt: {}", path.display()))?,
+            ),
+            None => None,
+        };
+        let content = jsonl_content(rows)?;
+
+        let mut global = Global::resolve()?;
+        let auth = Auth::ensure(cli, &mut global, AuthState::Existing).await?;
+        let project_header = auth.project_header(&project);
+        let state = OpenAiLocalProjectState::new(&project, project_header.as_deref())?;
+
+        let lock = Lock::read(&project)?;
+        let spinner = UiTheme::start_spinner("Syncing OpenAI evals");
+        let by_name = OpenAiLocalEvals::resolve(
+            &auth,
+            &state,
+            false,
+            project_header.clone(),
+            &lock,
+            Lock::project_key(project_header.as_deref()),
+        )
+        .await;
+        spinner.finish_and_clear();
+        let by_name = by_name?;
+        let remote = by_name.get(&spec.name).with_context(|| {
+            format!(
+                "Eval '{}' isn't synced to OpenAI yet, run `xeval eval` first",
+                spec.name
+            )
+        })?;
+
+        let local_hash = content_hash(&spec.to_openai_eval()?);
+        if content_hash(remote) != local_hash {
+            UiMessage::warn(&format!(
+                "Remote eval '{}' differs from {}, run `xeval eval` to sync it",
+                spec.name,
+                spec_path.display()
+            ));
+        }
+
+        let spinner = UiTheme::start_spinner(&format!("Uploading dataset: {}", spec.name));
+        let file = auth
+            .openai
+            .upload_file(
+                project_header.as_deref(),
+                &format!("{}.jsonl", spec.name),
+                OPENAI_FILE_PURPOSE_EVALS,
+                &content,
+            )
+            .await;
+        spinner.finish_and_clear();
+        let source = file_id_source(file?.id);
+        let data_source = match (&args.model, &prompt) {
+            (Some(model), Some(prompt)) => completions_data_source(model, prompt, source),
+            _ => jsonl_data_source(source),
+        };
+
+        let spinner = UiTheme::start_spinner(&format!("Starting run: {}", spec.name));
+        let mut run = auth
+            .openai
+            .create_eval_run(
+                project_header.as_deref(),
+                &remote.id,
+                &OpenAiEvalRunCreate {
+                    name: Some(spec.name.clone()),
+                    metadata: None,
+                    data_source,
+                },
+            )
+            .await?;
+
+        while !run.status.is_finished() {
+            spinner.set_message(format!(
+                "Running {}: {}/{} items",
+                spec.name,
+                run.result_counts.passed + run.result_counts.failed + run.result_counts.errored,
+                run.result_counts.total
+            ));
+            tokio::time::sleep(Duration::from_secs(args.poll_interval)).await;
+            run = auth
+                .openai
+                .get_eval_run(project_header.as_deref(), &remote.id, &run.id)
+                .await?;
+        }
+        spinner.finish_and_clear();
+
+        print_run_summary(&run)?;
+        Ok(())
+    }
+}
+
+fn jsonl_data_source(source: OpenAiEvalRunSource) -> OpenAiEvalRunDataSource {
+    OpenAiEvalRunDataSource::Jsonl(OpenAiEvalRunJsonlDataSource {
+        r#type: OpenAiEvalRunJsonlDataSourceType,
+        source,
+    })
+}
+
+fn completions_data_source(
+    model: &str,
+    prompt: &str,
+    source: OpenAiEvalRunSource,
+) -> OpenAiEvalRunDataSource {
+    OpenAiEvalRunDataSource::Completions(OpenAiEvalRunCompletionsDataSource {
+        r#type: OpenAiEvalRunCompletionsDataSourceType,
+        source,
+        input_messages: OpenAiEvalRunInputMessages::Template(OpenAiEvalRunInputMessagesTemplate {
+            r#type: OpenAiEvalRunInputMessagesTemplateType,
+            template: vec![OpenAiModelInput {
+                r#type: OpenAiMessageType,
+                role: OpenAiMessageRole::User,
+                content: OpenAiModelInputContent::String(translate_template_placeholders(prompt)),
+            }],
+        }),
+        model: model.to_string(),
+        sampling_params: Map::new(),
+    })
+}
+
+fn file_id_source(id: String) -> OpenAiEvalRunSource {
+    OpenAiEvalRunSource::FileId(OpenAiEvalRunFileIdSource {
+        r#type: OpenAiEvalRunFileIdSourceType,
+        id,
+    })
+}
+
+/// Dataset as an evals JSONL file. Rows may carry a precomputed `sample`
+/// object next to the item fields.
+fn jsonl_content(rows: Vec<Map<String, Value>>) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    for mut item in rows {
+        let sample = match item.remove("sample") {
+            Some(Value::Object(sample)) => Some(sample),
+            Some(other) => {
+                item.insert("sample".into(), other);
+                None
+            }
+            None => None,
+        };
+        serde_json::to_writer(&mut content, &OpenAiEvalRunContentItem { item, sample })
+            .context("Failed to serialize dataset row")?;
+        content.push(b'\n');
+    }
+    Ok(content)
+}
+
+fn print_run_summary(run: &OpenAiEvalRun) -> Result<()> {
+    let counts = &run.result_counts;
+    match run.status {
+        OpenAiEvalRunStatus::Completed => UiMessage::success(&format!(
+            "Run {} completed: {}/{} passed, {} failed, {} errored",
+            run.name, counts.passed, counts.total, counts.failed, counts.errored
+        )),
+        OpenAiEvalRunStatus::Canceled => UiMessage::warn(&format!("Run {} was canceled", run.name)),
+        _ => {
+            let message = run
+                .error
+                .as_ref()
+                .and_then(|err| err.message.clone())
+                .unwrap_or_else(|| "unknown error".to_string());
+            anyhow::bail!("Run {} failed: {}", run.name, message);
+        }
+    }
+
+    for result in run.per_testing_criteria_results.iter().flatten() {
+        let total = result.passed + result.failed;
+        let rate = if total == 0 {
+            0.0
+        } else {
+            result.passed as f64 / total as f64 * 100.0
+        };
+        UiMessage::info(&format!(
+            "{}: {}/{} passed ({:.0}%)",
+            result.testing_criteria, result.passed, total, rate
+        ));
+    }
+
+    if let Some(url) = &run.report_url {
+        UiMessage::info(&format!("Report: {url}"));
+    }
+
+    Ok(())
+}
@@ -6,6 +6,7 @@ mod command;
 mod openai;
 mod prelude;
 mod ui;
+mod watch;
 
 #[tokio::main]
 async fn main() {
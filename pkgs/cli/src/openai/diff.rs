@@ -0,0 +1,141 @@
+use super::plan::diff_value;
+use crate::prelude::*;
+use serde_json::Value;
+
+/// Structured differences between a remote eval and its local spec.
+#[derive(Debug, Clone, Default)]
+pub struct OpenAiEvalDiff {
+    /// Name and data source config changes.
+    pub config: Vec<OpenAiEvalChange>,
+    pub graders: Vec<OpenAiGraderDiff>,
+}
+
+#[derive(Debug, Clone)]
+pub enum OpenAiGraderDiff {
+    Added(String),
+    Removed(String),
+    Changed(String, Vec<OpenAiEvalChange>),
+}
+
+impl OpenAiEvalDiff {
+    /// Compare normalized evals, matching graders by name so reordering or
+    /// inserting one doesn't show up as every later grader changing.
+    pub fn new(remote: &OpenAiEval, local: &OpenAiEval) -> Self {
+        let remote = remote.normalized();
+        let local = local.normalized();
+
+        let mut config = Vec::new();
+        for key in ["name", "data_source_config"] {
+            diff_value(key, remote.get(key), local.get(key), &mut config);
+        }
+
+        let criteria = |eval: &Value| -> Vec<Value> {
+            eval.get("testing_criteria")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default()
+        };
+        let mut remaining = criteria(&remote);
+        let mut graders = Vec::new();
+        for grader in criteria(&local) {
+            let name = grader_name(&grader);
+            match remaining.iter().position(|r| grader_name(r) == name) {
+                Some(index) => {
+                    let previous = remaining.remove(index);
+                    let changes = json_diff(&previous, &grader);
+                    if !changes.is_empty() {
+                        graders.push(OpenAiGraderDiff::Changed(name, changes));
+                    }
+                }
+                None => graders.push(OpenAiGraderDiff::Added(name)),
+            }
+        }
+        graders.extend(
+            remaining
+                .iter()
+                .map(|grader| OpenAiGraderDiff::Removed(grader_name(grader))),
+        );
+
+        Self { config, graders }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.config.is_empty() && self.graders.is_empty()
+    }
+}
+
+fn grader_name(grader: &Value) -> String {
+    grader
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use xeval_openai::testing::{self, string_check_grader as grader};
+
+    fn eval(graders: Value) -> OpenAiEval {
+        testing::eval(
+            "eval_1",
+            json!({"name": "math", "testing_criteria": graders}),
+        )
+    }
+
+    #[test]
+    fn eval_diff_matches_graders_by_name() {
+        let remote = eval(json!([
+            grader("a", "1"),
+            grader("b", "2"),
+            grader("c", "3")
+        ]));
+        let local = eval(json!([
+            grader("c", "3"),
+            grader("a", "1"),
+            grader("b", "4"),
+            grader("d", "5")
+        ]));
+        let diff = OpenAiEvalDiff::new(&remote, &local);
+
+        assert!(diff.config.is_empty());
+        let graders: Vec<String> = diff
+            .graders
+            .iter()
+            .map(|grader| match grader {
+                OpenAiGraderDiff::Added(name) => format!("+{name}"),
+                OpenAiGraderDiff::Removed(name) => format!("-{name}"),
+                OpenAiGraderDiff::Changed(name, changes) => format!(
+                    "~{name} {}",
+                    changes
+                        .iter()
+                        .map(|c| c.path.as_str())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+            })
+            .collect();
+        assert_eq!(graders, vec!["~b reference", "+d"]);
+    }
+
+    #[test]
+    fn eval_diff_reports_removed_graders_and_config_changes() {
+        let remote = eval(json!([grader("a", "1"), grader("b", "2")]));
+        let mut local = eval(json!([grader("a", "1")]));
+        local.name = "maths".into();
+        let diff = OpenAiEvalDiff::new(&remote, &local);
+
+        assert_eq!(
+            diff.config,
+            vec![OpenAiEvalChange {
+                path: "name".into(),
+                old: Some(json!("math")),
+                new: Some(json!("maths")),
+            }]
+        );
+        assert!(matches!(&diff.graders[..], [OpenAiGraderDiff::Removed(name)] if name == "b"));
+        assert!(OpenAiEvalDiff::new(&remote, &remote).is_empty());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::prelude::*;
+use futures_util::{StreamExt, stream};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -11,6 +13,10 @@ struct OpenAiLocalEvalsFile {
     evals: Vec<OpenAiEval>,
 }
 
+/// Locked evals fetched at once by id, low enough to stay clear of rate
+/// limits.
+const FETCH_LOCKED_CONCURRENCY: usize = 8;
+
 pub struct OpenAiLocalEvals;
 
 impl OpenAiLocalEvals {
@@ -26,6 +32,7 @@ impl OpenAiLocalEvals {
         let is_stale = now - cache.updated_at > 5 * 60; // 5 minutes
 
         if !force && !is_stale {
+            Self::warn_unknown_types(&cache.evals);
             return Ok(cache.evals);
         }
 
@@ -35,8 +42,8 @@ impl OpenAiLocalEvals {
                 project: project.clone(),
                 after: None,
                 limit: Some(1),
-                order: Some("desc".to_string()),
-                order_by: Some("updated_at".to_string()),
+                order: Some("desc"),
+                order_by: Some("updated_at"),
             })
             .await
             .context("Failed to fetch latest eval page")?;
@@ -48,6 +55,7 @@ impl OpenAiLocalEvals {
         let remote_hash = remote_latest.as_ref().map(hash_eval).unwrap_or(0);
 
         if local_hash == remote_hash {
+            Self::warn_unknown_types(&cache.evals);
             return Ok(cache.evals);
         }
 
@@ -55,8 +63,8 @@ impl OpenAiLocalEvals {
             .openai
             .list_all_evals(&OpenAiListAllEvalsRequest {
                 project,
-                order: Some("desc".to_string()),
-                order_by: Some("updated_at".to_string()),
+                order: Some("desc"),
+                order_by: Some("updated_at"),
             })
             .await
             .context("Failed to fetch all evals")?;
@@ -66,8 +74,200 @@ impl OpenAiLocalEvals {
 
         state.evals.write_json(&cache)?;
 
+        Self::warn_unknown_types(&evals);
         Ok(evals)
     }
+
+    /// Warn about evals with graders or data source configs xeval doesn't
+    /// know, since they're kept as raw JSON and can't be pulled or diffed.
+    fn warn_unknown_types<'a>(evals: impl IntoIterator<Item = &'a OpenAiEval>) {
+        for eval in evals {
+            let unknown = eval.unknown_types();
+            if !unknown.is_empty() {
+                UiMessage::warn(&format!(
+                    "Eval '{}' ({}) uses unsupported types: {}. It's kept as raw JSON",
+                    eval.name,
+                    eval.id,
+                    unknown.join(", ")
+                ));
+            }
+        }
+    }
+
+    /// Index evals by `xeval_name` metadata, preferring the latest by `created_at`.
+    pub fn latest_by_name(evals: Vec<OpenAiEval>) -> HashMap<String, OpenAiEval> {
+        Self::latest_by(evals, |eval| {
+            eval.metadata.as_ref()?.get("xeval_name").cloned()
+        })
+    }
+
+    /// Index evals by a name picked by `name_of`, preferring the latest by
+    /// `created_at`. Evals without a name are skipped.
+    pub fn latest_by(
+        evals: Vec<OpenAiEval>,
+        name_of: impl Fn(&OpenAiEval) -> Option<String>,
+    ) -> HashMap<String, OpenAiEval> {
+        let mut by_name: HashMap<String, OpenAiEval> = HashMap::new();
+        for eval in evals {
+            let Some(name) = name_of(&eval) else {
+                continue;
+            };
+            let is_newer = by_name
+                .get(&name)
+                .is_none_or(|existing| eval.created_at > existing.created_at);
+            if is_newer {
+                by_name.insert(name, eval);
+            }
+        }
+        by_name
+    }
+
+    /// Current remote eval per spec name. The listing from `sync` may be
+    /// cached and miss evals created since, so locked evals missing from it
+    /// are fetched by id before resolving with `resolve_locked`.
+    pub async fn resolve(
+        auth: &Auth,
+        state: &OpenAiLocalProjectState<'_>,
+        force: bool,
+        project: Option<String>,
+        lock: &Lock,
+        project_key: &str,
+    ) -> Result<HashMap<String, OpenAiEval>> {
+        let mut evals = Self::sync(auth, state, force, project.clone()).await?;
+
+        let listed: HashSet<&str> = evals.iter().map(|eval| eval.id.as_str()).collect();
+        let missing: Vec<String> = lock
+            .evals
+            .values()
+            .filter_map(|entry| entry.remote.get(project_key))
+            .filter(|remote| !listed.contains(remote.id.as_str()))
+            .map(|remote| remote.id.clone())
+            .collect();
+
+        let mut fetches = stream::iter(missing)
+            .map(|id| {
+                let project = project.as_deref();
+                async move { auth.openai.get_eval(project, &id).await }
+            })
+            .buffer_unordered(FETCH_LOCKED_CONCURRENCY);
+        while let Some(eval) = fetches.next().await {
+            match eval {
+                Ok(eval) => {
+                    Self::warn_unknown_types([&eval]);
+                    evals.push(eval);
+                }
+                // Deleted, `resolve_locked` reports it
+                Err(OpenAiEvalsError::Http(status, _)) if status == "404" => {}
+                Err(err) => return Err(err).context("Failed to fetch locked eval"),
+            }
+        }
+
+        Ok(Self::resolve_locked(evals, lock, project_key))
+    }
+
+    /// Like `latest_by_name` but evals pinned in the lock file win over
+    /// `xeval_name` metadata. Disagreements are reported as drift.
+    pub fn resolve_locked(
+        evals: Vec<OpenAiEval>,
+        lock: &Lock,
+        project_key: &str,
+    ) -> HashMap<String, OpenAiEval> {
+        let by_id: HashMap<String, OpenAiEval> = evals
+            .iter()
+            .map(|eval| (eval.id.clone(), eval.clone()))
+            .collect();
+        let mut by_name = Self::latest_by_name(evals);
+
+        for (name, entry) in &lock.evals {
+            let Some(locked) = entry.remote.get(project_key) else {
+                continue;
+            };
+            let Some(eval) = by_id.get(&locked.id) else {
+                UiMessage::warn(&format!(
+                    "Drift: locked eval {} for '{}' no longer exists on OpenAI, using the latest by name",
+                    locked.id, name
+                ));
+                continue;
+            };
+            if let Some(drift) = Self::drift(name, locked, eval) {
+                UiMessage::warn(&drift);
+            }
+            if let Some(latest) = by_name.get(name).filter(|latest| latest.id != eval.id) {
+                UiMessage::warn(&format!(
+                    "Drift: '{}' is locked to {} but {} is newer on OpenAI",
+                    name, eval.id, latest.id
+                ));
+            }
+            by_name.insert(name.clone(), eval.clone());
+        }
+
+        by_name
+    }
+
+    /// Fetch locked evals by id, skipping the full eval listing. Returns
+    /// `None` when some name isn't locked or the remote side disagrees, so
+    /// the caller falls back to `resolve_locked` which reports the drift.
+    pub async fn fetch_locked(
+        auth: &Auth,
+        project: Option<&str>,
+        lock: &Lock,
+        project_key: &str,
+        names: &[String],
+    ) -> Result<Option<HashMap<String, OpenAiEval>>> {
+        let mut locked = Vec::with_capacity(names.len());
+        for name in names {
+            let Some(remote) = lock.remote(name, project_key) else {
+                return Ok(None);
+            };
+            locked.push((name, remote));
+        }
+
+        let mut fetches = stream::iter(locked)
+            .map(|(name, remote)| async move {
+                (
+                    name,
+                    remote,
+                    auth.openai.get_eval(project, &remote.id).await,
+                )
+            })
+            .buffer_unordered(FETCH_LOCKED_CONCURRENCY);
+
+        let mut by_name = HashMap::new();
+        while let Some((name, remote, eval)) = fetches.next().await {
+            let eval = match eval {
+                Ok(eval) => eval,
+                Err(OpenAiEvalsError::Http(status, _)) if status == "404" => return Ok(None),
+                Err(err) => return Err(err).context("Failed to fetch locked eval"),
+            };
+            if Self::drift(name, remote, &eval).is_some() {
+                return Ok(None);
+            }
+            by_name.insert(name.clone(), eval);
+        }
+        Self::warn_unknown_types(by_name.values());
+        Ok(Some(by_name))
+    }
+
+    /// Describe how a locked eval changed on OpenAI since it was locked:
+    /// its metadata names another spec or its content no longer matches
+    /// the locked hash. Evals without `xeval_name`, like ones pulled from
+    /// the dashboard, get stamped by the next sync and aren't drift.
+    fn drift(name: &str, locked: &LockRemote, eval: &OpenAiEval) -> Option<String> {
+        let remote_name = eval.metadata.as_ref().and_then(|md| md.get("xeval_name"));
+        if let Some(remote_name) = remote_name.filter(|remote_name| *remote_name != name) {
+            return Some(format!(
+                "Drift: locked eval {} for '{}' is named '{}' in its metadata",
+                eval.id, name, remote_name
+            ));
+        }
+        if content_hash(eval) != locked.hash {
+            return Some(format!(
+                "Drift: locked eval {} for '{}' was changed on OpenAI since it was locked",
+                eval.id, name
+            ));
+        }
+        None
+    }
 }
 
 fn now_unix() -> i64 {
@@ -82,3 +282,131 @@ fn hash_eval(eval: &OpenAiEval) -> u64 {
     eval.hash(&mut h);
     h.finish()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use xeval_openai::testing::{eval, named_eval, string_check_grader};
+    use xeval_types::EvalSpec;
+
+    fn lock(entries: &[(&str, &str, &OpenAiEval)]) -> Lock {
+        let mut lock = Lock::default();
+        for (name, project_key, eval) in entries {
+            lock.record(
+                name,
+                &format!("evals/{name}.yaml"),
+                "local",
+                project_key,
+                LockRemote {
+                    id: eval.id.clone(),
+                    hash: content_hash(eval),
+                },
+            );
+        }
+        lock
+    }
+
+    fn ids(by_name: &HashMap<String, OpenAiEval>) -> Vec<(&str, &str)> {
+        let mut ids: Vec<_> = by_name
+            .iter()
+            .map(|(name, eval)| (name.as_str(), eval.id.as_str()))
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn locked_evals_win_over_newer_ones() {
+        let locked = named_eval("eval_1", 1, "math");
+        let evals = vec![
+            locked.clone(),
+            named_eval("eval_2", 2, "math"),
+            named_eval("eval_3", 3, "text"),
+        ];
+        let lock = lock(&[("math", LOCK_DEFAULT_PROJECT, &locked)]);
+
+        let by_name = OpenAiLocalEvals::resolve_locked(evals, &lock, LOCK_DEFAULT_PROJECT);
+        assert_eq!(ids(&by_name), [("math", "eval_1"), ("text", "eval_3")]);
+    }
+
+    #[test]
+    fn missing_locked_evals_fall_back_to_latest_by_name() {
+        let gone = named_eval("eval_0", 0, "math");
+        let evals = vec![
+            named_eval("eval_1", 1, "math"),
+            named_eval("eval_2", 2, "math"),
+        ];
+        let lock = lock(&[("math", LOCK_DEFAULT_PROJECT, &gone)]);
+
+        let by_name = OpenAiLocalEvals::resolve_locked(evals, &lock, LOCK_DEFAULT_PROJECT);
+        assert_eq!(ids(&by_name), [("math", "eval_2")]);
+    }
+
+    #[test]
+    fn ignores_locks_of_other_projects() {
+        let locked = named_eval("eval_1", 1, "math");
+        let evals = vec![locked.clone(), named_eval("eval_2", 2, "math")];
+        let lock = lock(&[("math", "proj_other", &locked)]);
+
+        let by_name = OpenAiLocalEvals::resolve_locked(evals, &lock, LOCK_DEFAULT_PROJECT);
+        assert_eq!(ids(&by_name), [("math", "eval_2")]);
+    }
+
+    #[test]
+    fn drift_reports_renamed_and_changed_evals() {
+        let eval = named_eval("eval_1", 1, "math");
+        let locked = LockRemote {
+            id: eval.id.clone(),
+            hash: content_hash(&eval),
+        };
+        assert_eq!(OpenAiLocalEvals::drift("math", &locked, &eval), None);
+
+        let renamed = named_eval("eval_1", 1, "text");
+        assert!(
+            OpenAiLocalEvals::drift("math", &locked, &renamed)
+                .is_some_and(|drift| drift.contains("named 'text'"))
+        );
+
+        let mut changed = eval.clone();
+        changed.name = "maths".into();
+        assert!(
+            OpenAiLocalEvals::drift("math", &locked, &changed)
+                .is_some_and(|drift| drift.contains("was changed on OpenAI"))
+        );
+    }
+
+    #[test]
+    fn pulled_evals_sync_without_drift() {
+        // Created on the dashboard, so without any xeval metadata
+        let pulled = eval(
+            "eval_1",
+            json!({
+                "name": "math",
+                "data_source_config": {
+                    "type": "custom",
+                    "schema": {
+                        "type": "object",
+                        "properties": {"item": {"type": "object"}, "sample": {"type": "object"}},
+                        "required": ["item", "sample"],
+                    },
+                },
+                "testing_criteria": [string_check_grader("exact", "42")],
+            }),
+        );
+        let local = EvalSpec::from_openai_eval(&pulled)
+            .unwrap()
+            .to_openai_eval()
+            .unwrap();
+        let lock = lock(&[("math", LOCK_DEFAULT_PROJECT, &pulled)]);
+        let locked = lock.remote("math", LOCK_DEFAULT_PROJECT).unwrap();
+        assert_eq!(OpenAiLocalEvals::drift("math", locked, &pulled), None);
+
+        let evals = vec![pulled.clone(), named_eval("eval_2", 2, "text")];
+        let by_name = OpenAiLocalEvals::resolve_locked(evals, &lock, LOCK_DEFAULT_PROJECT);
+        assert_eq!(ids(&by_name), [("math", "eval_1"), ("text", "eval_2")]);
+
+        let plan = OpenAiEvalPlan::new("math", by_name.get("math"), &local, &content_hash(&local));
+        assert!(matches!(plan.action, OpenAiEvalPlanAction::UpdateMetadata));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::process::Command;
+
+pub const LINEAGE_PREVIOUS_KEY: &str = "xeval_previous";
+pub const LINEAGE_VERSION_KEY: &str = "xeval_version";
+pub const LINEAGE_COMMIT_KEY: &str = "xeval_commit";
+
+/// Links a replacement eval to the version it supersedes.
+pub struct OpenAiEvalLineage;
+
+impl OpenAiEvalLineage {
+    /// Insert lineage metadata for a new eval replacing `previous`.
+    pub fn stamp(
+        metadata: &mut BTreeMap<String, String>,
+        previous: Option<&OpenAiEval>,
+        commit: Option<&str>,
+    ) {
+        let version = previous.map(|eval| Self::version(eval) + 1).unwrap_or(1);
+        metadata.insert(LINEAGE_VERSION_KEY.into(), version.to_string());
+        if let Some(previous) = previous {
+            metadata.insert(LINEAGE_PREVIOUS_KEY.into(), previous.id.clone());
+        }
+        if let Some(commit) = commit {
+            metadata.insert(LINEAGE_COMMIT_KEY.into(), commit.to_string());
+        }
+    }
+
+    /// Version from metadata. Evals created before lineage tracking count as 1.
+    pub fn version(eval: &OpenAiEval) -> u32 {
+        Self::metadata(eval, LINEAGE_VERSION_KEY)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1)
+    }
+
+    pub fn previous(eval: &OpenAiEval) -> Option<&str> {
+        Self::metadata(eval, LINEAGE_PREVIOUS_KEY)
+    }
+
+    pub fn commit(eval: &OpenAiEval) -> Option<&str> {
+        Self::metadata(eval, LINEAGE_COMMIT_KEY)
+    }
+
+    /// All versions of the named eval, oldest first. Follows `previous`
+    /// links back from the latest version. Where a link is missing, broken
+    /// or circles back, the next older eval by `created_at` continues it.
+    pub fn chain(evals: Vec<OpenAiEval>, name: &str) -> Vec<OpenAiEval> {
+        let mut by_age: Vec<OpenAiEval> = evals
+            .into_iter()
+            .filter(|eval| Self::metadata(eval, "xeval_name") == Some(name))
+            .collect();
+        by_age.sort_by_key(|eval| (eval.created_at, Self::version(eval)));
+
+        let index: HashMap<&str, usize> = by_age
+            .iter()
+            .enumerate()
+            .map(|(i, eval)| (eval.id.as_str(), i))
+            .collect();
+        let mut visited = HashSet::new();
+        let mut order = Vec::with_capacity(by_age.len());
+        let mut next = by_age.len().checked_sub(1);
+        while let Some(i) = next {
+            visited.insert(i);
+            order.push(i);
+            next = Self::previous(&by_age[i])
+                .and_then(|id| index.get(id).copied())
+                .filter(|i| !visited.contains(i))
+                .or_else(|| (0..by_age.len()).rev().find(|i| !visited.contains(i)));
+        }
+
+        let mut by_age: Vec<Option<OpenAiEval>> = by_age.into_iter().map(Some).collect();
+        order
+            .into_iter()
+            .rev()
+            .filter_map(|i| by_age[i].take())
+            .collect()
+    }
+
+    fn metadata<'a>(eval: &'a OpenAiEval, key: &str) -> Option<&'a str> {
+        eval.metadata
+            .as_ref()
+            .and_then(|md| md.get(key))
+            .map(String::as_str)
+    }
+}
+
+/// Current git commit of the repository containing `path`, if any.
+pub fn git_commit(path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("HEAD")
+        .current_dir(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let commit = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!commit.is_empty()).then_some(commit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use xeval_openai::testing::{eval, named_eval};
+
+    fn version(id: &str, created_at: i64, version: u32, previous: Option<&str>) -> OpenAiEval {
+        let mut metadata = json!({"xeval_name": "math", LINEAGE_VERSION_KEY: version.to_string()});
+        if let Some(previous) = previous {
+            metadata[LINEAGE_PREVIOUS_KEY] = json!(previous);
+        }
+        eval(id, json!({"created_at": created_at, "metadata": metadata}))
+    }
+
+    fn ids(chain: &[OpenAiEval]) -> Vec<&str> {
+        chain.iter().map(|eval| eval.id.as_str()).collect()
+    }
+
+    #[test]
+    fn stamps_lineage_metadata() {
+        let mut metadata = BTreeMap::new();
+        OpenAiEvalLineage::stamp(&mut metadata, None, None);
+        assert_eq!(
+            metadata,
+            BTreeMap::from([(LINEAGE_VERSION_KEY.into(), "1".into())])
+        );
+
+        let previous = version("eval_2", 2, 2, Some("eval_1"));
+        let mut metadata = BTreeMap::new();
+        OpenAiEvalLineage::stamp(&mut metadata, Some(&previous), Some("abc123"));
+        assert_eq!(
+            metadata,
+            BTreeMap::from([
+                (LINEAGE_COMMIT_KEY.into(), "abc123".into()),
+                (LINEAGE_PREVIOUS_KEY.into(), "eval_2".into()),
+                (LINEAGE_VERSION_KEY.into(), "3".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn versions_default_to_one() {
+        assert_eq!(
+            OpenAiEvalLineage::version(&named_eval("eval_1", 1, "math")),
+            1
+        );
+        let mut eval = named_eval("eval_1", 1, "math");
+        eval.metadata
+            .as_mut()
+            .unwrap()
+            .insert(LINEAGE_VERSION_KEY.into(), "latest".into());
+        assert_eq!(OpenAiEvalLineage::version(&eval), 1);
+    }
+
+    #[test]
+    fn chain_follows_previous_links() {
+        // Created within the same second, so only the links tell the order
+        let evals = vec![
+            version("eval_c", 5, 3, Some("eval_a")),
+            named_eval("eval_x", 5, "text"),
+            version("eval_b", 5, 1, None),
+            version("eval_a", 5, 2, Some("eval_b")),
+        ];
+        assert_eq!(
+            ids(&OpenAiEvalLineage::chain(evals, "math")),
+            ["eval_b", "eval_a", "eval_c"]
+        );
+    }
+
+    #[test]
+    fn chain_falls_back_to_creation_order_for_missing_links() {
+        let evals = vec![
+            version("eval_3", 3, 3, Some("eval_deleted")),
+            named_eval("eval_1", 1, "math"),
+            version("eval_2", 2, 2, Some("eval_1")),
+        ];
+        assert_eq!(
+            ids(&OpenAiEvalLineage::chain(evals, "math")),
+            ["eval_1", "eval_2", "eval_3"]
+        );
+    }
+
+    #[test]
+    fn chain_survives_cycles() {
+        let evals = vec![
+            version("eval_1", 1, 1, Some("eval_3")),
+            version("eval_2", 2, 2, Some("eval_1")),
+            version("eval_3", 3, 3, Some("eval_2")),
+        ];
+        assert_eq!(
+            ids(&OpenAiEvalLineage::chain(evals, "math")),
+            ["eval_1", "eval_2", "eval_3"]
+        );
+
+        let evals = vec![
+            version("eval_1", 1, 1, Some("eval_2")),
+            version("eval_2", 2, 2, Some("eval_1")),
+            version("eval_3", 3, 3, Some("eval_3")),
+        ];
+        assert_eq!(
+            ids(&OpenAiEvalLineage::chain(evals, "math")),
+            ["eval_1", "eval_2", "eval_3"]
+        );
+    }
+}
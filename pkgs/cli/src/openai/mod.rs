@@ -6,3 +6,12 @@ pub use evals::*;
 
 mod state;
 pub use state::*;
+
+mod plan;
+pub use plan::*;
+
+mod diff;
+pub use diff::*;
+
+mod lineage;
+pub use lineage::*;
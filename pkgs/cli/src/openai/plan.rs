@@ -0,0 +1,212 @@
+use crate::prelude::*;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// What syncing a local spec would do to its remote eval.
+#[derive(Debug, Clone)]
+pub enum OpenAiEvalPlanAction {
+    /// No remote eval carries this name yet.
+    Create,
+    /// Core fields changed, so a new eval version gets created.
+    Replace(OpenAiEvalDiff),
+    /// Content matches but the `xeval_hash` metadata is stale.
+    UpdateMetadata,
+    Unchanged,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenAiEvalPlan {
+    pub name: String,
+    pub action: OpenAiEvalPlanAction,
+}
+
+impl OpenAiEvalPlan {
+    /// Match a spec's local eval against its current remote eval. Graders
+    /// are matched by name like in `xeval diff`, so reordering them doesn't
+    /// create a new version.
+    pub fn new(
+        name: &str,
+        remote: Option<&OpenAiEval>,
+        local: &OpenAiEval,
+        local_hash: &str,
+    ) -> Self {
+        let action = match remote {
+            None => OpenAiEvalPlanAction::Create,
+            Some(remote) => {
+                let diff = OpenAiEvalDiff::new(remote, local);
+                let remote_hash = remote.metadata.as_ref().and_then(|m| m.get("xeval_hash"));
+                if !diff.is_empty() {
+                    OpenAiEvalPlanAction::Replace(diff)
+                } else if remote_hash.is_none_or(|hash| hash != local_hash) {
+                    OpenAiEvalPlanAction::UpdateMetadata
+                } else {
+                    OpenAiEvalPlanAction::Unchanged
+                }
+            }
+        };
+
+        Self {
+            name: name.to_string(),
+            action,
+        }
+    }
+}
+
+/// A single changed leaf between two JSON documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenAiEvalChange {
+    /// Path such as `testing_criteria[0].pass_threshold`.
+    pub path: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// Diff two JSON values down to their changed leaves.
+pub fn json_diff(old: &Value, new: &Value) -> Vec<OpenAiEvalChange> {
+    let mut out = Vec::new();
+    diff_value("", Some(old), Some(new), &mut out);
+    out
+}
+
+pub(crate) fn diff_value(
+    path: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    out: &mut Vec<OpenAiEvalChange>,
+) {
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff_value(&path, old.get(key), new.get(key), out);
+            }
+        }
+
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for i in 0..old.len().max(new.len()) {
+                diff_value(&format!("{path}[{i}]"), old.get(i), new.get(i), out);
+            }
+        }
+
+        (old, new) if old != new => out.push(OpenAiEvalChange {
+            path: path.to_string(),
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use xeval_openai::testing::{self, string_check_grader as grader};
+
+    fn change(path: &str, old: Option<Value>, new: Option<Value>) -> OpenAiEvalChange {
+        OpenAiEvalChange {
+            path: path.into(),
+            old,
+            new,
+        }
+    }
+
+    #[test]
+    fn json_diff_reports_added_removed_and_changed_fields() {
+        let old = json!({"name": "math", "threshold": 0.5, "removed": true});
+        let new = json!({"name": "math", "threshold": 0.8, "added": [1]});
+        assert_eq!(
+            json_diff(&old, &new),
+            vec![
+                change("added", None, Some(json!([1]))),
+                change("removed", Some(json!(true)), None),
+                change("threshold", Some(json!(0.5)), Some(json!(0.8))),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_diff_ignores_key_order() {
+        let old = json!({"a": 1, "b": {"c": 2, "d": 3}});
+        let new = json!({"b": {"d": 3, "c": 2}, "a": 1});
+        assert!(json_diff(&old, &new).is_empty());
+    }
+
+    fn plan(remote: Option<&OpenAiEval>, local: &OpenAiEval) -> OpenAiEvalPlanAction {
+        OpenAiEvalPlan::new("math", remote, local, &content_hash(local)).action
+    }
+
+    fn eval(graders: Value, metadata: Value) -> OpenAiEval {
+        testing::eval(
+            "eval_1",
+            json!({"name": "math", "testing_criteria": graders, "metadata": metadata}),
+        )
+    }
+
+    #[test]
+    fn plans_creating_missing_evals() {
+        let local = eval(json!([grader("a", "1")]), Value::Null);
+        assert!(matches!(plan(None, &local), OpenAiEvalPlanAction::Create));
+    }
+
+    #[test]
+    fn plans_new_versions_for_changed_content() {
+        let local = eval(json!([grader("a", "1"), grader("b", "2")]), Value::Null);
+        let remote = eval(
+            json!([grader("a", "0")]),
+            json!({"xeval_hash": content_hash(&local)}),
+        );
+        let OpenAiEvalPlanAction::Replace(diff) = plan(Some(&remote), &local) else {
+            panic!("expected a new version");
+        };
+        assert_eq!(diff.graders.len(), 2);
+    }
+
+    #[test]
+    fn plans_metadata_updates_for_stale_hashes() {
+        let local = eval(json!([grader("a", "1")]), Value::Null);
+        let remote = eval(json!([grader("a", "1")]), Value::Null);
+        assert!(matches!(
+            plan(Some(&remote), &local),
+            OpenAiEvalPlanAction::UpdateMetadata
+        ));
+
+        let remote = eval(json!([grader("a", "1")]), json!({"xeval_hash": "stale"}));
+        assert!(matches!(
+            plan(Some(&remote), &local),
+            OpenAiEvalPlanAction::UpdateMetadata
+        ));
+    }
+
+    #[test]
+    fn plans_nothing_for_synced_evals() {
+        let local = eval(json!([grader("a", "1"), grader("b", "2")]), Value::Null);
+        let remote = eval(
+            json!([grader("a", "1"), grader("b", "2")]),
+            json!({"xeval_hash": content_hash(&local)}),
+        );
+        assert!(matches!(
+            plan(Some(&remote), &local),
+            OpenAiEvalPlanAction::Unchanged
+        ));
+    }
+
+    #[test]
+    fn reordered_graders_are_not_a_new_version() {
+        let local = eval(json!([grader("b", "2"), grader("a", "1")]), Value::Null);
+        let remote = eval(
+            json!([grader("a", "1"), grader("b", "2")]),
+            json!({"xeval_hash": content_hash(&local)}),
+        );
+        assert!(matches!(
+            plan(Some(&remote), &local),
+            OpenAiEvalPlanAction::Unchanged
+        ));
+    }
+}
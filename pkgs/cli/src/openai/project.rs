@@ -3,18 +3,20 @@ use crate::prelude::*;
 pub struct OpenAiLocalProject {}
 
 impl OpenAiLocalProject {
-    pub async fn select(auth: &Auth) -> Result<()> {
-        let spinner = UiTheme::start_spinner("Loading OpenAI projects");
+    /// Let the user pick one of the active OpenAI projects. Returns `None`
+    /// for the default project.
+    pub async fn select(auth: &Auth, current: Option<&str>) -> Result<Option<String>> {
+        let projects = Self::list(auth).await?;
+        UiOpenAiProject::inquire_project(&projects, current)
+    }
 
-        let projects = auth
-            .openai
-            .list_all_projects(Default::default())
-            .await?;
+    pub async fn list(auth: &Auth) -> Result<Vec<OpenAiProject>> {
+        let spinner = UiTheme::start_spinner("Loading OpenAI projects");
 
-        println!("PROJECTS {:?}", projects);
+        let projects = auth.openai.list_all_projects(Default::default()).await;
 
         spinner.finish_and_clear();
 
-        Ok(())
+        projects.context("Failed to load OpenAI projects")
     }
 }
@@ -10,8 +10,14 @@ pub struct OpenAiLocalProjectState<'a> {
 }
 
 impl<'a> OpenAiLocalProjectState<'a> {
-    pub fn new(project: &'a Project) -> Result<Self> {
-        let relative: StdPathBuf = StdPathBuf::from(OPENAI_DIRNAME).join(OPENAI_EVALS_FILENAME);
+    /// Evals are cached per OpenAI project, the default project keeps the
+    /// top-level cache.
+    pub fn new(project: &'a Project, openai_project: Option<&str>) -> Result<Self> {
+        let mut relative: StdPathBuf = StdPathBuf::from(OPENAI_DIRNAME);
+        if let Some(id) = openai_project {
+            relative.push(id);
+        }
+        relative.push(OPENAI_EVALS_FILENAME);
         let file = ProjectStateFile::ensure(project, relative)?;
         Ok(Self { evals: file })
     }
@@ -6,7 +6,7 @@ pub use anyhow::{Context, Result};
 pub use clap::Parser;
 pub use console::{StyledObject, style};
 pub use dialoguer::{
-    Confirm, Password, Input,
+    Confirm, FuzzySelect, Input, Password,
     theme::{ColorfulTheme, Theme},
 };
 pub use indicatif::{ProgressBar, ProgressIterator, ProgressStyle};
@@ -18,6 +18,7 @@ pub use crate::cli::*;
 pub use crate::command::*;
 pub use crate::openai::*;
 pub use crate::ui::*;
+pub use crate::watch::*;
 
 pub use xeval_global::prelude::*;
 pub use xeval_openai::prelude::*;
@@ -0,0 +1,38 @@
+use crate::prelude::*;
+
+pub struct UiDiff {}
+
+impl UiDiff {
+    pub fn print(name: &str, diff: &OpenAiEvalDiff) {
+        if diff.is_empty() {
+            println!("{} {} is up to date", style("=").dim(), name);
+            return;
+        }
+
+        println!("{} {}", style("~").yellow(), name);
+        Self::print_changes(diff);
+    }
+
+    /// Data source and grader changes, grouped by grader.
+    pub fn print_changes(diff: &OpenAiEvalDiff) {
+        if !diff.config.is_empty() {
+            println!("  {}", style("data source").bold());
+            UiPlan::print_changes(&diff.config);
+        }
+
+        for grader in &diff.graders {
+            match grader {
+                OpenAiGraderDiff::Added(name) => {
+                    println!("  {} grader {}", style("+").green(), style(name).bold())
+                }
+                OpenAiGraderDiff::Removed(name) => {
+                    println!("  {} grader {}", style("-").red(), style(name).bold())
+                }
+                OpenAiGraderDiff::Changed(name, changes) => {
+                    println!("  {} grader {}", style("~").yellow(), style(name).bold());
+                    UiPlan::print_changes(changes);
+                }
+            }
+        }
+    }
+}
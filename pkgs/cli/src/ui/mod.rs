@@ -9,3 +9,12 @@ pub use theme::*;
 
 mod config;
 pub use config::*;
+
+mod plan;
+pub use plan::*;
+
+mod prune;
+pub use prune::*;
+
+mod diff;
+pub use diff::*;
@@ -18,3 +18,35 @@ impl UiOpenAiToken {
         Ok(should_retry)
     }
 }
+
+pub struct UiOpenAiProject {}
+
+impl UiOpenAiProject {
+    /// Pick a project, `None` stands for the default project.
+    pub fn inquire_project(
+        projects: &[OpenAiProject],
+        current: Option<&str>,
+    ) -> Result<Option<String>> {
+        let mut items = vec!["Default project".to_string()];
+        items.extend(
+            projects
+                .iter()
+                .map(|project| format!("{} ({})", project.name, project.id)),
+        );
+
+        let default = current
+            .and_then(|id| projects.iter().position(|project| project.id == id))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+
+        let selection = FuzzySelect::with_theme(UiTheme::for_dialoguer())
+            .with_prompt("OpenAI project")
+            .items(&items)
+            .default(default)
+            .interact()?;
+
+        Ok(selection
+            .checked_sub(1)
+            .map(|index| projects[index].id.clone()))
+    }
+}
@@ -0,0 +1,79 @@
+use crate::prelude::*;
+use serde_json::Value;
+
+pub struct UiPlan {}
+
+impl UiPlan {
+    pub fn print(plans: &[OpenAiEvalPlan]) {
+        let mut create = 0;
+        let mut replace = 0;
+        let mut metadata = 0;
+        let mut unchanged = 0;
+
+        for plan in plans {
+            match &plan.action {
+                OpenAiEvalPlanAction::Create => {
+                    create += 1;
+                    println!("{} {} will be created", style("+").green(), plan.name);
+                }
+                OpenAiEvalPlanAction::Replace(diff) => {
+                    replace += 1;
+                    println!(
+                        "{} {} will get a new version",
+                        style("~").yellow(),
+                        plan.name
+                    );
+                    UiDiff::print_changes(diff);
+                }
+                OpenAiEvalPlanAction::UpdateMetadata => {
+                    metadata += 1;
+                    println!(
+                        "{} {} will have its metadata updated",
+                        style("~").cyan(),
+                        plan.name
+                    );
+                }
+                OpenAiEvalPlanAction::Unchanged => {
+                    unchanged += 1;
+                    println!("{} {} is unchanged", style("=").dim(), plan.name);
+                }
+            }
+        }
+
+        println!();
+        UiMessage::info(&format!(
+            "Plan: {create} to create, {replace} new version(s), {metadata} metadata update(s), {unchanged} unchanged"
+        ));
+    }
+
+    pub fn print_changes(changes: &[OpenAiEvalChange]) {
+        for change in changes {
+            match (&change.old, &change.new) {
+                (Some(old), Some(new)) => println!(
+                    "    {} {}: {} -> {}",
+                    style("~").yellow(),
+                    change.path,
+                    style(Self::format_value(old)).red(),
+                    style(Self::format_value(new)).green()
+                ),
+                (None, Some(new)) => println!(
+                    "    {} {}: {}",
+                    style("+").green(),
+                    change.path,
+                    style(Self::format_value(new)).green()
+                ),
+                (Some(old), None) => println!(
+                    "    {} {}: {}",
+                    style("-").red(),
+                    change.path,
+                    style(Self::format_value(old)).red()
+                ),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn format_value(value: &Value) -> String {
+        serde_json::to_string(value).unwrap_or_default()
+    }
+}
@@ -0,0 +1,12 @@
+use crate::prelude::*;
+
+pub struct UiPrune {}
+
+impl UiPrune {
+    pub fn confirm(action: &str, count: usize) -> Result<bool, dialoguer::Error> {
+        Confirm::with_theme(UiTheme::for_dialoguer())
+            .with_prompt(format!("{action} {count} eval(s) on OpenAI?"))
+            .default(false)
+            .interact()
+    }
+}
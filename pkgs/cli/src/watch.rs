@@ -0,0 +1,98 @@
+use crate::prelude::*;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Quiet period after the last file event before changes are reported.
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub struct FileWatcher {
+    root: PathBuf,
+    watcher: RecommendedWatcher,
+    watched: HashSet<PathBuf>,
+    events: mpsc::UnboundedReceiver<notify::Result<Event>>,
+}
+
+impl FileWatcher {
+    /// Start watching `root` recursively.
+    pub fn new(root: &Path) -> Result<Self> {
+        let root = normalize_path(root);
+        let (tx, events) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        })
+        .context("Failed to start file watcher")?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", root.display()))?;
+
+        Ok(Self {
+            root,
+            watcher,
+            watched: HashSet::new(),
+            events,
+        })
+    }
+
+    /// Also watch a file that may live outside of the root.
+    pub fn add(&mut self, path: &Path) -> Result<()> {
+        let path = normalize_path(path);
+        if path.starts_with(&self.root) {
+            return Ok(());
+        }
+        let Some(dir) = path.parent().map(|p| p.to_path_buf()) else {
+            return Ok(());
+        };
+        if self.watched.insert(dir.clone()) {
+            self.watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .with_context(|| format!("Failed to watch {}", dir.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Wait for the next batch of changed paths. Events are collected until
+    /// none arrive for `WATCH_DEBOUNCE`.
+    pub async fn next_changes(&mut self) -> Result<BTreeSet<PathBuf>> {
+        let mut changed = BTreeSet::new();
+        while changed.is_empty() {
+            let event = self
+                .events
+                .recv()
+                .await
+                .context("File watcher stopped unexpectedly")?;
+            collect_event_paths(event, &mut changed);
+        }
+        while let Ok(Some(event)) = tokio::time::timeout(WATCH_DEBOUNCE, self.events.recv()).await {
+            collect_event_paths(event, &mut changed);
+        }
+        Ok(changed)
+    }
+}
+
+fn collect_event_paths(event: notify::Result<Event>, out: &mut BTreeSet<PathBuf>) {
+    match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+            out.extend(event.paths.iter().map(|p| normalize_path(p)));
+        }
+        Ok(_) => {}
+        Err(err) => UiMessage::warn(&format!("File watcher error: {err}")),
+    }
+}
+
+/// Canonicalize when possible so watcher and glob paths compare equal.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Path relative to `root` with forward slashes, as recorded in the lock file.
+pub fn relative_path(root: &Path, path: &Path) -> String {
+    let root = normalize_path(root);
+    let path = normalize_path(path);
+    path.strip_prefix(&root)
+        .unwrap_or(&path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
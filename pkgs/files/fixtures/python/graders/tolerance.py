@@ -0,0 +1,6 @@
+def grade(sample, item) -> float:
+    try:
+        output = float(sample["output_text"].strip())
+    except ValueError:
+        return 0.0
+    return 1.0 if abs(output - float(item["answer"])) < 1e-6 else 0.0
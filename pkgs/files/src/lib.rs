@@ -1,19 +1,73 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result, anyhow, bail};
 use globwalk::GlobWalkerBuilder;
+use serde_json::{Number, Value};
 use std::path::{Path, PathBuf};
-use xeval_types::EvalSpec;
+use xeval_types::{DataRow, DataRowError, EvalSpec, SimpleField, SimpleFieldType, TestSpec};
+
+/// Maximum number of row errors listed when a dataset fails validation.
+const MAX_REPORTED_ROW_ERRORS: usize = 20;
 
 pub fn parse_eval_spec_from_str(yaml: &str) -> Result<EvalSpec> {
     let spec: EvalSpec = serde_yaml::from_str(yaml)?;
+    spec.validate()?;
     Ok(spec)
 }
 
 pub fn parse_eval_spec_from_path(path: impl AsRef<Path>) -> Result<EvalSpec> {
+    let path = path.as_ref();
     let text = std::fs::read_to_string(path)?;
-    parse_eval_spec_from_str(&text)
+    let mut spec = parse_eval_spec_from_str(&text)?;
+    let base = path.parent().unwrap_or_else(|| Path::new("."));
+    load_test_sources(&mut spec, base)?;
+    Ok(spec)
+}
+
+/// Inline external files referenced by tests (e.g. Python grader sources),
+/// resolving them relative to `base`.
+pub fn load_test_sources(spec: &mut EvalSpec, base: impl AsRef<Path>) -> Result<()> {
+    let base = base.as_ref();
+    for test in spec.tests.iter_mut() {
+        load_test_source(test, base)?;
+    }
+    Ok(())
+}
+
+fn load_test_source(test: &mut TestSpec, base: &Path) -> Result<()> {
+    match test {
+        TestSpec::Python {
+            source,
+            source_code,
+            ..
+        } => {
+            let source_path = base.join(&*source);
+            let code = std::fs::read_to_string(&source_path).with_context(|| {
+                format!("Failed to read python grader: {}", source_path.display())
+            })?;
+            *source_code = Some(code);
+        }
+
+        TestSpec::Multi { tests, .. } => {
+            for test in tests.values_mut() {
+                load_test_source(test, base)?;
+            }
+        }
+
+        _ => {}
+    }
+    Ok(())
 }
 
 pub fn find_eval_specs(glob: &str, root: impl AsRef<Path>) -> Result<Vec<(PathBuf, EvalSpec)>> {
+    let mut out = Vec::new();
+    for path in find_eval_spec_paths(glob, root)? {
+        let spec = parse_eval_spec_from_path(&path)
+            .with_context(|| format!("Failed to parse eval spec: {}", path.display()))?;
+        out.push((path, spec));
+    }
+    Ok(out)
+}
+
+pub fn find_eval_spec_paths(glob: &str, root: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
     let root = root.as_ref();
     // Normalize common leading"./" and backslashes in patterns
     let normalized = normalize_glob(glob);
@@ -21,14 +75,226 @@ pub fn find_eval_specs(glob: &str, root: impl AsRef<Path>) -> Result<Vec<(PathBu
         .case_insensitive(true)
         .build()
         .with_context(|| format!("Invalid glob pattern: {glob}"))?;
-    let mut out = Vec::new();
-    for entry in walker.filter_map(Result::ok).filter(|e| e.file_type().is_file()) {
-        let path = entry.path().to_path_buf();
-        let spec = parse_eval_spec_from_path(&path)
-            .with_context(|| format!("Failed to parse eval spec: {}", path.display()))?;
-        out.push((path, spec));
+    Ok(walker
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.path().to_path_buf())
+        .collect())
+}
+
+/// Directory a glob pattern is anchored at, i.e. `./evals/**/*.yaml` in
+/// `root` resolves to `root/evals`.
+pub fn glob_base_dir(glob: &str, root: impl AsRef<Path>) -> PathBuf {
+    let normalized = normalize_glob(glob);
+    let base: Vec<&str> = normalized
+        .split('/')
+        .take_while(|part| !part.contains(['*', '?', '[', '{']))
+        .collect();
+    // The last literal part is a file name unless the pattern continues
+    let dir = if base.len() == normalized.split('/').count() {
+        &base[..base.len().saturating_sub(1)]
+    } else {
+        &base[..]
+    };
+    dir.iter()
+        .fold(root.as_ref().to_path_buf(), |path, part| path.join(part))
+}
+
+/// Write a spec as YAML along with the Python grader sources it inlines.
+pub fn write_eval_spec(path: impl AsRef<Path>, spec: &EvalSpec) -> Result<()> {
+    let path = path.as_ref();
+    let base = path.parent().unwrap_or_else(|| Path::new("."));
+    for test in &spec.tests {
+        write_test_source(test, base)?;
     }
-    Ok(out)
+    let yaml = serde_yaml::to_string(spec).context("Failed to serialize eval spec")?;
+    std::fs::create_dir_all(base)
+        .with_context(|| format!("Failed to create {}", base.display()))?;
+    std::fs::write(path, yaml).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn write_test_source(test: &TestSpec, base: &Path) -> Result<()> {
+    match test {
+        TestSpec::Python {
+            source,
+            source_code: Some(code),
+            ..
+        } => {
+            let path = base.join(source);
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("Failed to create {}", dir.display()))?;
+            }
+            std::fs::write(&path, code)
+                .with_context(|| format!("Failed to write grader source: {}", path.display()))
+        }
+
+        TestSpec::Multi { tests, .. } => {
+            for test in tests.values() {
+                write_test_source(test, base)?;
+            }
+            Ok(())
+        }
+
+        _ => Ok(()),
+    }
+}
+
+/// Files besides the spec itself that affect it: the dataset and Python
+/// grader sources.
+pub fn referenced_paths(spec_path: impl AsRef<Path>, spec: &EvalSpec) -> Vec<PathBuf> {
+    let spec_path = spec_path.as_ref();
+    let base = spec_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut out: Vec<PathBuf> = resolve_dataset_path(spec_path, spec).into_iter().collect();
+    for test in &spec.tests {
+        collect_test_source_paths(test, base, &mut out);
+    }
+    out
+}
+
+fn collect_test_source_paths(test: &TestSpec, base: &Path, out: &mut Vec<PathBuf>) {
+    match test {
+        TestSpec::Python { source, .. } => out.push(base.join(source)),
+
+        TestSpec::Multi { tests, .. } => {
+            for test in tests.values() {
+                collect_test_source_paths(test, base, out);
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// Load the spec dataset, either inline `data` rows or the `dataset` file
+/// resolved relative to `spec_path`, and validate every row against the
+/// spec schema. Returns `None` when the spec has no dataset.
+pub fn load_eval_dataset(
+    spec_path: impl AsRef<Path>,
+    spec: &EvalSpec,
+) -> Result<Option<Vec<DataRow>>> {
+    let (source, rows) = match (&spec.data, &spec.dataset) {
+        (Some(rows), _) => ("inline data".to_string(), rows.clone()),
+        (None, Some(dataset)) => {
+            let path = resolve_dataset_path(spec_path, spec).unwrap_or_default();
+            (dataset.clone(), read_dataset_file(&path, spec)?)
+        }
+        (None, None) => return Ok(None),
+    };
+
+    let errors: Vec<DataRowError> = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(i, row)| spec.validate_row(i + 1, row))
+        .collect();
+    if !errors.is_empty() {
+        let mut message = format!("Invalid rows in {source} of {}:", spec.name);
+        for err in errors.iter().take(MAX_REPORTED_ROW_ERRORS) {
+            message.push_str(&format!("\n  {err}"));
+        }
+        if errors.len() > MAX_REPORTED_ROW_ERRORS {
+            message.push_str(&format!(
+                "\n  ...and {} more",
+                errors.len() - MAX_REPORTED_ROW_ERRORS
+            ));
+        }
+        bail!(message);
+    }
+
+    Ok(Some(rows))
+}
+
+/// Resolve the dataset file path referenced by the spec, if any.
+pub fn resolve_dataset_path(spec_path: impl AsRef<Path>, spec: &EvalSpec) -> Option<PathBuf> {
+    let base = spec_path
+        .as_ref()
+        .parent()
+        .unwrap_or_else(|| Path::new("."));
+    spec.dataset.as_ref().map(|dataset| base.join(dataset))
+}
+
+fn read_dataset_file(path: &Path, spec: &EvalSpec) -> Result<Vec<DataRow>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read dataset: {}", path.display()))?;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("jsonl") => parse_jsonl_dataset(&text),
+        Some("csv") => parse_csv_dataset(&text, spec),
+        _ => Err(anyhow!(
+            "Unsupported dataset format (expected .jsonl or .csv): {}",
+            path.display()
+        )),
+    }
+}
+
+fn parse_jsonl_dataset(text: &str) -> Result<Vec<DataRow>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(row)) => Ok(row),
+            Ok(_) => Err(anyhow!("line {}: expected a JSON object", i + 1)),
+            Err(err) => Err(anyhow!("line {}: {err}", i + 1)),
+        })
+        .collect()
+}
+
+fn parse_csv_dataset(text: &str, spec: &EvalSpec) -> Result<Vec<DataRow>> {
+    let fields = spec.item_fields();
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    let headers = reader.headers()?.clone();
+    let mut rows = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record.with_context(|| format!("row {}", i + 1))?;
+        let mut row = DataRow::new();
+        for (column, cell) in headers.iter().zip(record.iter()) {
+            let value = match fields.get(column) {
+                Some(field) => csv_cell_to_value(field, cell)
+                    .with_context(|| format!("row {}, column `{column}`", i + 1))?,
+                None => Some(Value::String(cell.to_string())),
+            };
+            if let Some(value) = value {
+                row.insert(column.to_string(), value);
+            }
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// CSV cells are untyped, so coerce them using the declared field type.
+/// Empty cells become missing (optional) or `null` (nullable) values.
+fn csv_cell_to_value(field: &SimpleField, cell: &str) -> Result<Option<Value>> {
+    if cell.is_empty() && field.r#type != SimpleFieldType::String {
+        return Ok(match (field.optional, field.nullable) {
+            (true, _) => None,
+            (false, true) => Some(Value::Null),
+            (false, false) => Some(Value::String(String::new())),
+        });
+    }
+
+    let value = match field.r#type {
+        SimpleFieldType::String => Value::String(cell.to_string()),
+        SimpleFieldType::Number => cell
+            .parse::<i64>()
+            .map(Number::from)
+            .ok()
+            .or_else(|| cell.parse::<f64>().ok().and_then(Number::from_f64))
+            .map(Value::Number)
+            .unwrap_or_else(|| Value::String(cell.to_string())),
+        SimpleFieldType::Boolean => match cell {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::String(cell.to_string()),
+        },
+        SimpleFieldType::Array | SimpleFieldType::Object => {
+            serde_json::from_str(cell).context("Failed to parse JSON cell")?
+        }
+    };
+    Ok(Some(value))
 }
 
 fn normalize_glob(glob: &str) -> String {
@@ -54,4 +320,51 @@ mod tests {
         let spec = parse_eval_spec_from_str(yaml).expect("parse");
         assert_yaml_snapshot!("math_yaml_spec", &spec);
     }
+
+    #[test]
+    fn inlines_python_grader_source() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/python/tolerance.yaml");
+        let spec = parse_eval_spec_from_path(&path).expect("parse");
+        let Some(TestSpec::Python { source_code, .. }) = spec.tests.first() else {
+            panic!("expected python test");
+        };
+        let expected = include_str!("../fixtures/python/graders/tolerance.py");
+        assert_eq!(source_code.as_deref(), Some(expected));
+        assert_eq!(
+            referenced_paths(&path, &spec),
+            vec![path.parent().unwrap().join("./graders/tolerance.py")]
+        );
+    }
+
+    #[test]
+    fn resolves_glob_base_dir() {
+        let root = Path::new("/project");
+        assert_eq!(
+            glob_base_dir("./evals/**/*.yaml", root),
+            Path::new("/project/evals")
+        );
+        assert_eq!(glob_base_dir("*.yaml", root), root);
+        assert_eq!(
+            glob_base_dir("specs/math.yaml", root),
+            Path::new("/project/specs")
+        );
+    }
+
+    #[test]
+    fn loads_and_validates_csv_dataset() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/dataset/math.yaml");
+        let spec = parse_eval_spec_from_path(&path).expect("parse");
+        let rows = load_eval_dataset(&path, &spec)
+            .expect("load")
+            .expect("rows");
+        assert_yaml_snapshot!("math_csv_dataset", &rows);
+
+        let mut invalid = spec.clone();
+        invalid.dataset = Some("invalid.jsonl".into());
+        let err = load_eval_dataset(&path, &invalid).expect_err("invalid rows");
+        assert_eq!(
+            err.to_string(),
+            "Invalid rows in invalid.jsonl of math:\n  row 2, column `b`: expected number, got \"two\"\n  row 3, column `answer`: missing required value"
+        );
+    }
 }
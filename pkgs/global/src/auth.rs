@@ -4,6 +4,19 @@ use crate::prelude::*;
 
 pub const GLOBAL_AUTH_FILENAME: &str = "auth.toml";
 
+/// Profile stored in the top-level tables of the auth file.
+pub const GLOBAL_AUTH_DEFAULT_PROFILE: &str = "default";
+
+/// Table holding OpenAI credentials and settings of a profile.
+pub const GLOBAL_AUTH_OPENAI_NAME: &str = "openai";
+
+/// Provider tables a profile can hold.
+const GLOBAL_AUTH_PROVIDER_NAMES: [&str; 1] = [GLOBAL_AUTH_OPENAI_NAME];
+
+const GLOBAL_AUTH_PROFILES_NAME: &str = "profiles";
+
+const GLOBAL_AUTH_DEFAULT_PROFILE_NAME: &str = "default_profile";
+
 #[derive(Error, Debug)]
 pub enum GlobalAuthError {
     #[error("Failed to parse {0}")]
@@ -39,27 +52,237 @@ impl GlobalAuth {
         Ok(GlobalAuth { doc })
     }
 
-    pub fn get(&self, name: &str) -> Option<&Table> {
-        self.doc.get(name).and_then(|item| item.as_table())
+    pub fn get(&self, profile: &str, name: &str) -> Option<&Table> {
+        self.profile(profile)
+            .and_then(|table| table.get(name))
+            .and_then(|item| item.as_table())
     }
 
-    pub fn update<UpdateFn>(&mut self, name: &str, update: UpdateFn) -> Result<()>
+    pub fn update<UpdateFn>(&mut self, profile: &str, name: &str, update: UpdateFn) -> Result<()>
     where
         UpdateFn: FnOnce(&mut Table) -> Result<()>,
     {
-        let mut table = self
-            .doc
+        let mut root = self.doc.as_table_mut();
+        if profile != GLOBAL_AUTH_DEFAULT_PROFILE {
+            root = Self::implicit_table(root, GLOBAL_AUTH_PROFILES_NAME)?;
+            root = Self::implicit_table(root, profile)?;
+        }
+        let table = root
             .entry(name)
             .or_insert(Item::Table(Table::new()))
             .as_table_mut()
             .with_context(|| format!("Failed to access {name} as table"))?;
-        update(&mut table)?;
+        update(table)?;
         Ok(())
     }
 
+    /// Names of all stored profiles, the default profile first.
+    pub fn profiles(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        if !self.default_profile_keys().is_empty() {
+            names.push(GLOBAL_AUTH_DEFAULT_PROFILE.to_string());
+        }
+        if let Some(profiles) = self.profiles_table() {
+            names.extend(profiles.iter().map(|(key, _)| key.to_string()));
+        }
+        names
+    }
+
+    pub fn has_profile(&self, profile: &str) -> bool {
+        self.profiles().iter().any(|name| name == profile)
+    }
+
+    /// Profile used when none is selected explicitly.
+    pub fn default_profile(&self) -> &str {
+        self.doc
+            .get(GLOBAL_AUTH_DEFAULT_PROFILE_NAME)
+            .and_then(|item| item.as_str())
+            .unwrap_or(GLOBAL_AUTH_DEFAULT_PROFILE)
+    }
+
+    pub fn set_default_profile(&mut self, profile: &str) {
+        if profile == GLOBAL_AUTH_DEFAULT_PROFILE {
+            self.doc.remove(GLOBAL_AUTH_DEFAULT_PROFILE_NAME);
+        } else {
+            self.doc[GLOBAL_AUTH_DEFAULT_PROFILE_NAME] = value(profile);
+        }
+    }
+
+    /// Remove all stored credentials of a profile. Returns whether anything
+    /// was removed.
+    pub fn remove_profile(&mut self, profile: &str) -> bool {
+        let removed = if profile == GLOBAL_AUTH_DEFAULT_PROFILE {
+            let keys = self.default_profile_keys();
+            keys.iter().for_each(|key| {
+                self.doc.remove(key);
+            });
+            !keys.is_empty()
+        } else {
+            self.doc
+                .get_mut(GLOBAL_AUTH_PROFILES_NAME)
+                .and_then(|item| item.as_table_mut())
+                .and_then(|profiles| profiles.remove(profile))
+                .is_some()
+        };
+        if self.default_profile() == profile {
+            self.set_default_profile(GLOBAL_AUTH_DEFAULT_PROFILE);
+        }
+        removed
+    }
+
+    /// Provider tables stored at the top level for the default profile.
+    fn default_profile_keys(&self) -> Vec<&'static str> {
+        GLOBAL_AUTH_PROVIDER_NAMES
+            .into_iter()
+            .filter(|name| self.doc.get(name).is_some_and(|item| item.is_table()))
+            .collect()
+    }
+
+    fn profile(&self, profile: &str) -> Option<&Table> {
+        if profile == GLOBAL_AUTH_DEFAULT_PROFILE {
+            return Some(self.doc.as_table());
+        }
+        self.profiles_table()
+            .and_then(|profiles| profiles.get(profile))
+            .and_then(|item| item.as_table())
+    }
+
+    fn profiles_table(&self) -> Option<&Table> {
+        self.doc
+            .get(GLOBAL_AUTH_PROFILES_NAME)
+            .and_then(|item| item.as_table())
+    }
+
+    fn implicit_table<'a>(parent: &'a mut Table, name: &str) -> Result<&'a mut Table> {
+        parent
+            .entry(name)
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
+            .as_table_mut()
+            .with_context(|| format!("Failed to access {name} as table"))
+    }
+
     pub fn persist(&self, dir: &GlobalDir) -> Result<(), GlobalAuthError> {
         let path = Self::resolve_path(dir);
         fs::write(&path, self.doc.to_string()).map_err(|_| GlobalAuthError::Write(path))?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth(toml: &str) -> GlobalAuth {
+        GlobalAuth {
+            doc: toml.parse().unwrap(),
+        }
+    }
+
+    const AUTH: &str = r#"
+default_profile = "work"
+
+[openai]
+api_token = "sk-default"
+
+[profiles.work.openai]
+api_token = "sk-work"
+
+[profiles.personal.openai]
+api_token = "sk-personal"
+"#;
+
+    #[test]
+    fn lists_default_profile_first() {
+        assert_eq!(auth(AUTH).profiles(), ["default", "work", "personal"]);
+        assert_eq!(
+            auth("[profiles.work.openai]\napi_token = \"sk-work\"\n").profiles(),
+            ["work"]
+        );
+        assert!(auth("").profiles().is_empty());
+    }
+
+    #[test]
+    fn sets_default_profile() {
+        let mut auth = auth(AUTH);
+        assert_eq!(auth.default_profile(), "work");
+
+        auth.set_default_profile("personal");
+        assert_eq!(auth.default_profile(), "personal");
+
+        auth.set_default_profile(GLOBAL_AUTH_DEFAULT_PROFILE);
+        assert_eq!(auth.default_profile(), GLOBAL_AUTH_DEFAULT_PROFILE);
+        assert!(auth.doc.get(GLOBAL_AUTH_DEFAULT_PROFILE_NAME).is_none());
+    }
+
+    #[test]
+    fn removes_named_profile_only() {
+        let mut auth = auth(AUTH);
+        assert!(auth.remove_profile("work"));
+
+        assert_eq!(auth.profiles(), ["default", "personal"]);
+        assert!(
+            auth.get(GLOBAL_AUTH_DEFAULT_PROFILE, GLOBAL_AUTH_OPENAI_NAME)
+                .is_some()
+        );
+        assert!(auth.get("personal", GLOBAL_AUTH_OPENAI_NAME).is_some());
+        // The removed profile was the default one
+        assert_eq!(auth.default_profile(), GLOBAL_AUTH_DEFAULT_PROFILE);
+
+        assert!(!auth.remove_profile("work"));
+    }
+
+    #[test]
+    fn removes_default_profile_only() {
+        let mut auth = auth(&format!("{AUTH}\n[unrelated]\nkey = 1\n"));
+        assert!(auth.remove_profile(GLOBAL_AUTH_DEFAULT_PROFILE));
+
+        assert_eq!(auth.profiles(), ["work", "personal"]);
+        assert!(
+            auth.get(GLOBAL_AUTH_DEFAULT_PROFILE, GLOBAL_AUTH_OPENAI_NAME)
+                .is_none()
+        );
+        assert!(auth.get("work", GLOBAL_AUTH_OPENAI_NAME).is_some());
+        assert!(auth.get("personal", GLOBAL_AUTH_OPENAI_NAME).is_some());
+        assert!(auth.doc.get("unrelated").is_some());
+        assert_eq!(auth.default_profile(), "work");
+
+        assert!(!auth.remove_profile(GLOBAL_AUTH_DEFAULT_PROFILE));
+    }
+
+    #[test]
+    fn updates_profiles_independently() {
+        let mut auth = auth("");
+        auth.update("work", GLOBAL_AUTH_OPENAI_NAME, |table| {
+            table["api_token"] = value("sk-work");
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(auth.profiles(), ["work"]);
+        assert!(
+            auth.get(GLOBAL_AUTH_DEFAULT_PROFILE, GLOBAL_AUTH_OPENAI_NAME)
+                .is_none()
+        );
+
+        auth.update(
+            GLOBAL_AUTH_DEFAULT_PROFILE,
+            GLOBAL_AUTH_OPENAI_NAME,
+            |table| {
+                table["api_token"] = value("sk-default");
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(auth.profiles(), ["default", "work"]);
+        let token = |profile| {
+            auth.get(profile, GLOBAL_AUTH_OPENAI_NAME)
+                .and_then(|table| table.get("api_token"))
+                .and_then(|item| item.as_str())
+        };
+        assert_eq!(token(GLOBAL_AUTH_DEFAULT_PROFILE), Some("sk-default"));
+        assert_eq!(token("work"), Some("sk-work"));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::prelude::*;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[derive(Error, Debug)]
+pub enum GlobalCredentialError {
+    #[error("Failed to run credential helper `{0}`: {1}")]
+    Spawn(String, std::io::Error),
+
+    #[error("Credential helper `{0}` failed to {1}: {2}")]
+    Failed(String, GlobalCredentialVerb, String),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum GlobalCredentialVerb {
+    Get,
+    Store,
+    Erase,
+}
+
+impl std::fmt::Display for GlobalCredentialVerb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlobalCredentialVerb::Get => write!(f, "get"),
+            GlobalCredentialVerb::Store => write!(f, "store"),
+            GlobalCredentialVerb::Erase => write!(f, "erase"),
+        }
+    }
+}
+
+/// External command that keeps secrets, modeled after git credential
+/// helpers. The verb is appended to the command, which runs in the shell.
+///
+/// The helper receives `key=value` lines on stdin: `provider` and `profile`,
+/// plus `token` for `store`. `get` prints the token to stdout, printing
+/// nothing means the helper has no token. Exiting with an error is reported
+/// along with the helper's stderr.
+#[derive(Debug, Clone)]
+pub struct GlobalCredentialHelper {
+    command: String,
+}
+
+impl GlobalCredentialHelper {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn get(
+        &self,
+        provider: &str,
+        profile: &str,
+    ) -> Result<Option<String>, GlobalCredentialError> {
+        let input = Self::input(provider, profile, None);
+        let output = self.run(GlobalCredentialVerb::Get, &input)?;
+        Ok(output
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(String::from))
+    }
+
+    pub fn store(
+        &self,
+        provider: &str,
+        profile: &str,
+        token: &str,
+    ) -> Result<(), GlobalCredentialError> {
+        let input = Self::input(provider, profile, Some(token));
+        self.run(GlobalCredentialVerb::Store, &input).map(|_| ())
+    }
+
+    pub fn erase(&self, provider: &str, profile: &str) -> Result<(), GlobalCredentialError> {
+        let input = Self::input(provider, profile, None);
+        self.run(GlobalCredentialVerb::Erase, &input).map(|_| ())
+    }
+
+    fn input(provider: &str, profile: &str, token: Option<&str>) -> String {
+        let mut input = format!("provider={provider}\nprofile={profile}\n");
+        if let Some(token) = token {
+            input.push_str(&format!("token={token}\n"));
+        }
+        input
+    }
+
+    fn run(
+        &self,
+        verb: GlobalCredentialVerb,
+        input: &str,
+    ) -> Result<String, GlobalCredentialError> {
+        let script = format!("{} {verb}", self.command);
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(&script);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(&script);
+            command
+        };
+
+        let spawn_error = |err| GlobalCredentialError::Spawn(self.command.clone(), err);
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // Helpers are free to ignore their input
+            let _ = stdin.write_all(input.as_bytes());
+        }
+        let output = child.wait_with_output().map_err(spawn_error)?;
+
+        if !output.status.success() {
+            return Err(GlobalCredentialError::Failed(
+                self.command.clone(),
+                verb,
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Helper that keeps tokens in files next to it and logs its input.
+    fn helper(dir: &tempfile::TempDir) -> GlobalCredentialHelper {
+        let path = dir.path().join("helper.sh");
+        fs::write(
+            &path,
+            r#"dir=$(dirname "$0")
+input=$(cat)
+echo "$1" >> "$dir/log"
+echo "$input" >> "$dir/log"
+file="$dir/$(echo "$input" | grep '^profile=' | cut -d= -f2)"
+case "$1" in
+  get) if [ -f "$file" ]; then cat "$file"; fi ;;
+  store) echo "$input" | grep '^token=' | cut -d= -f2 > "$file" ;;
+  erase) rm -f "$file" ;;
+esac
+"#,
+        )
+        .unwrap();
+        GlobalCredentialHelper::new(format!("sh {}", path.display()))
+    }
+
+    #[test]
+    fn stores_gets_and_erases_tokens() {
+        let dir = tempfile::tempdir().unwrap();
+        let helper = helper(&dir);
+
+        assert_eq!(helper.get("openai", "work").unwrap(), None);
+        helper.store("openai", "work", "sk-work").unwrap();
+        assert_eq!(
+            helper.get("openai", "work").unwrap(),
+            Some("sk-work".to_string())
+        );
+        assert_eq!(helper.get("openai", "personal").unwrap(), None);
+        helper.erase("openai", "work").unwrap();
+        assert_eq!(helper.get("openai", "work").unwrap(), None);
+
+        let log = fs::read_to_string(dir.path().join("log")).unwrap();
+        assert!(log.starts_with("get\nprovider=openai\nprofile=work\n"));
+        assert!(log.contains("store\nprovider=openai\nprofile=work\ntoken=sk-work\n"));
+        assert!(log.contains("erase\nprovider=openai\nprofile=work\n"));
+    }
+
+    #[test]
+    fn reports_failures_with_stderr() {
+        let helper = GlobalCredentialHelper::new("echo locked >&2; exit 1; true");
+        let err = helper.get("openai", "work").unwrap_err();
+        assert!(matches!(
+            &err,
+            GlobalCredentialError::Failed(_, GlobalCredentialVerb::Get, stderr) if stderr == "locked"
+        ));
+        assert!(matches!(
+            helper.store("openai", "work", "sk-work"),
+            Err(GlobalCredentialError::Failed(
+                _,
+                GlobalCredentialVerb::Store,
+                _
+            ))
+        ));
+        assert!(matches!(
+            helper.erase("openai", "work"),
+            Err(GlobalCredentialError::Failed(
+                _,
+                GlobalCredentialVerb::Erase,
+                _
+            ))
+        ));
+    }
+}
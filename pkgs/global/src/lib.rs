@@ -1,4 +1,5 @@
 pub mod auth;
+pub mod credential;
 pub mod dir;
 pub mod index;
 pub mod prelude;
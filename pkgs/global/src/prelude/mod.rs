@@ -2,5 +2,6 @@ mod internal;
 pub(crate) use internal::*;
 
 pub use crate::auth::*;
+pub use crate::credential::*;
 pub use crate::dir::*;
 pub use crate::index::*;
@@ -1,12 +1,19 @@
 use crate::prelude::*;
 
-const GLOBAL_AUTH_OPENAI_NAME: &str = "openai";
-
 const GLOBAL_AUTH_OPENAI_API_TOKEN_NAME: &str = "api_token";
 
+const GLOBAL_AUTH_OPENAI_ORGANIZATION_NAME: &str = "organization";
+
+const GLOBAL_AUTH_OPENAI_PROJECT_NAME: &str = "project";
+
+const GLOBAL_AUTH_OPENAI_CREDENTIAL_HELPER_NAME: &str = "credential_helper";
+
 // TODO: Consider using admin tokens for projects management
 // const GLOBAL_AUTH_OPENAI_ADMIN_TOKEN_NAME: &str = "admin_token";
 
+/// Environment variables checked for a token, in order.
+pub const OPENAI_API_KEY_ENV_VARS: [&str; 2] = ["XEVAL_OPENAI_API_KEY", "OPENAI_API_KEY"];
+
 #[derive(Error, Debug)]
 pub enum OpenAiAuthError {
     #[error(transparent)]
@@ -14,39 +21,216 @@ pub enum OpenAiAuthError {
 
     #[error("Failed to update OpenAI auth token: {0}")]
     Update(anyhow::Error),
+
+    #[error(transparent)]
+    Credential(#[from] GlobalCredentialError),
+}
+
+/// Where the token came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpenAiAuthSource {
+    /// Passed explicitly, i.e. with `--token`.
+    Flag,
+    /// Read from the named environment variable.
+    Env(&'static str),
+    /// Returned by the profile's credential helper.
+    Helper,
+    /// Read from the global auth file.
+    Stored,
+    /// Entered interactively.
+    Prompt,
+}
+
+impl std::fmt::Display for OpenAiAuthSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpenAiAuthSource::Flag => write!(f, "--token"),
+            OpenAiAuthSource::Env(name) => write!(f, "${name}"),
+            OpenAiAuthSource::Helper => write!(f, "credential helper"),
+            OpenAiAuthSource::Stored => write!(f, "{GLOBAL_AUTH_FILENAME}"),
+            OpenAiAuthSource::Prompt => write!(f, "prompt"),
+        }
+    }
 }
 
 pub struct OpenAiAuth {
     token: OpenAiToken,
+    source: OpenAiAuthSource,
+    profile: String,
+    organization: Option<String>,
+    project: Option<String>,
 }
 
 impl OpenAiAuth {
-    pub fn new(token: OpenAiToken) -> Self {
-        Self { token }
+    pub fn new(token: OpenAiToken, source: OpenAiAuthSource) -> Self {
+        Self {
+            token,
+            source,
+            profile: GLOBAL_AUTH_DEFAULT_PROFILE.to_string(),
+            organization: None,
+            project: None,
+        }
+    }
+
+    /// Resolve the token from, in order: the explicit `token`, the
+    /// environment, the credential helper and the global auth file. Without
+    /// `profile` the default profile from the global auth file is used.
+    ///
+    /// A failing credential helper is skipped in favor of the stored token,
+    /// its error is only returned when there's no token to fall back to.
+    pub fn detect(
+        global: &Global,
+        profile: Option<&str>,
+        token: Option<OpenAiToken>,
+    ) -> Result<Option<Self>, OpenAiAuthError> {
+        let profile = profile.unwrap_or_else(|| global.auth.default_profile());
+        let auth = |token: OpenAiToken, source| {
+            Some(Self::new(token, source).with_profile(global, profile))
+        };
+
+        if let Some(token) = token {
+            return Ok(auth(token, OpenAiAuthSource::Flag));
+        }
+
+        if let Some((token, name)) = Self::env_token() {
+            return Ok(auth(token.into(), OpenAiAuthSource::Env(name)));
+        }
+
+        let helper_error = match Self::credential_helper(global, profile)
+            .map(|helper| helper.get(GLOBAL_AUTH_OPENAI_NAME, profile))
+        {
+            Some(Ok(Some(token))) => return Ok(auth(token.into(), OpenAiAuthSource::Helper)),
+            Some(Err(err)) => Some(err),
+            Some(Ok(None)) | None => None,
+        };
+
+        match (Self::stored(global, profile), helper_error) {
+            (None, Some(err)) => Err(err.into()),
+            (stored, _) => Ok(stored),
+        }
     }
 
-    pub fn detect(global: &Global) -> Option<Self> {
+    /// Credentials stored in the global auth file for `profile`.
+    pub fn stored(global: &Global, profile: &str) -> Option<Self> {
+        let token = Self::stored_value(global, profile, GLOBAL_AUTH_OPENAI_API_TOKEN_NAME)?;
+        Some(Self::new(token.into(), OpenAiAuthSource::Stored).with_profile(global, profile))
+    }
+
+    /// Attach the profile along with its organization and default project.
+    pub fn with_profile(mut self, global: &Global, profile: &str) -> Self {
+        self.organization =
+            Self::stored_value(global, profile, GLOBAL_AUTH_OPENAI_ORGANIZATION_NAME)
+                .map(String::from);
+        self.project =
+            Self::stored_value(global, profile, GLOBAL_AUTH_OPENAI_PROJECT_NAME).map(String::from);
+        self.profile = profile.to_string();
+        self
+    }
+
+    fn env_token() -> Option<(String, &'static str)> {
+        OPENAI_API_KEY_ENV_VARS.iter().find_map(|name| {
+            std::env::var(name)
+                .ok()
+                .map(|token| token.trim().to_string())
+                .filter(|token| !token.is_empty())
+                .map(|token| (token, *name))
+        })
+    }
+
+    /// Credential helper configured for `profile`.
+    pub fn credential_helper(global: &Global, profile: &str) -> Option<GlobalCredentialHelper> {
+        Self::stored_value(global, profile, GLOBAL_AUTH_OPENAI_CREDENTIAL_HELPER_NAME)
+            .map(GlobalCredentialHelper::new)
+    }
+
+    fn stored_value<'a>(global: &'a Global, profile: &str, key: &str) -> Option<&'a str> {
         global
             .auth
-            .get(GLOBAL_AUTH_OPENAI_NAME)
-            .and_then(|table| table.get(GLOBAL_AUTH_OPENAI_API_TOKEN_NAME))
+            .get(profile, GLOBAL_AUTH_OPENAI_NAME)
+            .and_then(|table| table.get(key))
             .and_then(|item| item.as_str())
-            .and_then(|token| Some(Self::new(token.into())))
     }
 
     pub fn token(&self) -> &str {
         self.token.as_str()
     }
 
+    pub fn masked_token(&self) -> String {
+        self.token.masked()
+    }
+
+    pub fn source(&self) -> &OpenAiAuthSource {
+        &self.source
+    }
+
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    pub fn organization(&self) -> Option<&str> {
+        self.organization.as_deref()
+    }
+
+    /// Default project of the profile.
+    pub fn project(&self) -> Option<&str> {
+        self.project.as_deref()
+    }
+
+    /// Add the credential headers to a request.
+    pub fn authorize(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let req = req.bearer_auth(self.token());
+        match &self.organization {
+            Some(organization) => req.header("OpenAI-Organization", organization),
+            None => req,
+        }
+    }
+
+    /// Store the token with the profile's credential helper or, without
+    /// one, in the global auth file.
     pub fn persist(&self, global: &mut Global) -> Result<(), OpenAiAuthError> {
+        let helper = Self::credential_helper(global, &self.profile);
+        if let Some(helper) = &helper {
+            helper.store(GLOBAL_AUTH_OPENAI_NAME, &self.profile, self.token.as_str())?;
+        }
         global
             .auth
-            .update(GLOBAL_AUTH_OPENAI_NAME, |table: &mut Table| {
-                table[GLOBAL_AUTH_OPENAI_API_TOKEN_NAME] = value(self.token.as_str());
-                Ok(())
-            })
-            .map_err(|err| OpenAiAuthError::Update(err))?;
+            .update(
+                &self.profile,
+                GLOBAL_AUTH_OPENAI_NAME,
+                |table: &mut Table| {
+                    match helper {
+                        // Never leave a plaintext copy next to the helper
+                        Some(_) => {
+                            table.remove(GLOBAL_AUTH_OPENAI_API_TOKEN_NAME);
+                        }
+                        None => {
+                            table[GLOBAL_AUTH_OPENAI_API_TOKEN_NAME] = value(self.token.as_str());
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(OpenAiAuthError::Update)?;
         global.persist_auth()?;
         Ok(())
     }
+
+    /// Remove the profile's token from the credential helper and the global
+    /// auth file. Other profile settings are kept.
+    pub fn erase(global: &mut Global, profile: &str) -> Result<(), OpenAiAuthError> {
+        if let Some(helper) = Self::credential_helper(global, profile) {
+            helper.erase(GLOBAL_AUTH_OPENAI_NAME, profile)?;
+        }
+        if global.auth.get(profile, GLOBAL_AUTH_OPENAI_NAME).is_some() {
+            global
+                .auth
+                .update(profile, GLOBAL_AUTH_OPENAI_NAME, |table: &mut Table| {
+                    table.remove(GLOBAL_AUTH_OPENAI_API_TOKEN_NAME);
+                    Ok(())
+                })
+                .map_err(OpenAiAuthError::Update)?;
+            global.persist_auth()?;
+        }
+        Ok(())
+    }
 }
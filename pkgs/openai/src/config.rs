@@ -0,0 +1,286 @@
+use crate::prelude::*;
+use reqwest::{Certificate, Proxy};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+pub const OPENAI_DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+pub const OPENAI_DEFAULT_CONNECT_TIMEOUT: u64 = 10;
+
+pub const OPENAI_DEFAULT_READ_TIMEOUT: u64 = 60;
+
+pub const OPENAI_DEFAULT_USER_AGENT: &str = concat!("xeval/", env!("CARGO_PKG_VERSION"));
+
+/// Environment variables checked for a base URL, in order.
+pub const OPENAI_BASE_URL_ENV_VARS: [&str; 2] = ["XEVAL_OPENAI_BASE_URL", "OPENAI_BASE_URL"];
+
+/// Environment variable with extra headers as comma or newline separated
+/// `Name=value` pairs, e.g. `X-Team=evals,X-Env=ci`.
+pub const OPENAI_HEADERS_ENV_VAR: &str = "XEVAL_OPENAI_HEADERS";
+
+const GLOBAL_AUTH_OPENAI_BASE_URL_NAME: &str = "base_url";
+
+const GLOBAL_AUTH_OPENAI_HEADERS_NAME: &str = "headers";
+
+const GLOBAL_AUTH_OPENAI_CONNECT_TIMEOUT_NAME: &str = "connect_timeout";
+
+const GLOBAL_AUTH_OPENAI_READ_TIMEOUT_NAME: &str = "read_timeout";
+
+const GLOBAL_AUTH_OPENAI_PROXY_NAME: &str = "proxy";
+
+const GLOBAL_AUTH_OPENAI_CA_BUNDLE_NAME: &str = "ca_bundle";
+
+const GLOBAL_AUTH_OPENAI_USER_AGENT_NAME: &str = "user_agent";
+
+#[derive(Error, Debug)]
+pub enum OpenAiConfigError {
+    #[error("Invalid proxy URL {0}: {1}")]
+    Proxy(String, reqwest::Error),
+
+    #[error("Failed to read CA bundle {0}: {1}")]
+    CaBundleRead(PathBuf, std::io::Error),
+
+    #[error("Failed to parse CA bundle {0}: {1}")]
+    CaBundleParse(PathBuf, reqwest::Error),
+
+    #[error("Failed to build HTTP client: {0}")]
+    Client(reqwest::Error),
+}
+
+/// Where and how to reach the API. Lets xeval talk to OpenAI-compatible
+/// gateways, proxies or a local mock server.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpenAiConfig {
+    /// API base URL, `OPENAI_DEFAULT_BASE_URL` if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Headers sent with every request.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Connect timeout in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<u64>,
+    /// Timeout in seconds for each read of a response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_timeout: Option<u64>,
+    /// Proxy URL for all requests. `HTTPS_PROXY` and friends apply otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// PEM file with extra root certificates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// How failed requests are retried.
+    #[serde(default, skip_serializing_if = "OpenAiRetryConfig::is_empty")]
+    pub retry: OpenAiRetryConfig,
+}
+
+impl OpenAiConfig {
+    /// Settings stored in the `openai` table of an auth profile.
+    pub fn from_profile(global: &Global, profile: &str) -> Self {
+        let Some(table) = global.auth.get(profile, GLOBAL_AUTH_OPENAI_NAME) else {
+            return Default::default();
+        };
+        let str_value = |key: &str| {
+            table
+                .get(key)
+                .and_then(|item| item.as_str())
+                .map(String::from)
+        };
+        let secs_value = |key: &str| {
+            table
+                .get(key)
+                .and_then(|item| item.as_integer())
+                .and_then(|secs| u64::try_from(secs).ok())
+        };
+        let headers = table
+            .get(GLOBAL_AUTH_OPENAI_HEADERS_NAME)
+            .and_then(|item| item.as_table_like())
+            .map(|headers| {
+                headers
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        Some((name.to_string(), value.as_str()?.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            base_url: str_value(GLOBAL_AUTH_OPENAI_BASE_URL_NAME),
+            headers,
+            connect_timeout: secs_value(GLOBAL_AUTH_OPENAI_CONNECT_TIMEOUT_NAME),
+            read_timeout: secs_value(GLOBAL_AUTH_OPENAI_READ_TIMEOUT_NAME),
+            proxy: str_value(GLOBAL_AUTH_OPENAI_PROXY_NAME),
+            ca_bundle: str_value(GLOBAL_AUTH_OPENAI_CA_BUNDLE_NAME).map(PathBuf::from),
+            user_agent: str_value(GLOBAL_AUTH_OPENAI_USER_AGENT_NAME),
+            retry: OpenAiRetryConfig::from_table(table),
+        }
+    }
+
+    /// Layer `other` on top of this config. Its settings win and its headers
+    /// replace headers with the same name.
+    pub fn merge(self, other: OpenAiConfig) -> Self {
+        let mut headers = self.headers;
+        headers.extend(other.headers);
+        Self {
+            base_url: other.base_url.or(self.base_url),
+            headers,
+            connect_timeout: other.connect_timeout.or(self.connect_timeout),
+            read_timeout: other.read_timeout.or(self.read_timeout),
+            proxy: other.proxy.or(self.proxy),
+            ca_bundle: other.ca_bundle.or(self.ca_bundle),
+            user_agent: other.user_agent.or(self.user_agent),
+            retry: self.retry.merge(other.retry),
+        }
+    }
+
+    /// Apply the base URL and headers from the environment, if set. They
+    /// win over any config, so the precedence is: environment, `xeval.toml`,
+    /// then the auth profile.
+    pub fn with_env(self) -> Self {
+        self.with_vars(|name| std::env::var(name).ok())
+    }
+
+    fn with_vars(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        let base_url = OPENAI_BASE_URL_ENV_VARS.iter().find_map(|name| {
+            var(name)
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+        });
+        if base_url.is_some() {
+            self.base_url = base_url;
+        }
+        if let Some(headers) = var(OPENAI_HEADERS_ENV_VAR) {
+            self.headers.extend(Self::parse_headers(&headers));
+        }
+        self
+    }
+
+    /// Parse `Name=value` pairs separated by commas or newlines, skipping
+    /// malformed ones.
+    fn parse_headers(headers: &str) -> impl Iterator<Item = (String, String)> {
+        headers.split([',', '\n']).filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| (name.to_string(), value.trim().to_string()))
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .unwrap_or(OPENAI_DEFAULT_BASE_URL)
+            .trim_end_matches('/')
+    }
+
+    /// Absolute URL of an API path such as `evals`.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url(), path.trim_start_matches('/'))
+    }
+
+    /// HTTP client shared by all requests.
+    pub fn client(&self) -> Result<Client, OpenAiConfigError> {
+        let connect_timeout = self
+            .connect_timeout
+            .unwrap_or(OPENAI_DEFAULT_CONNECT_TIMEOUT);
+        let read_timeout = self.read_timeout.unwrap_or(OPENAI_DEFAULT_READ_TIMEOUT);
+        let mut builder = Client::builder()
+            .connect_timeout(Duration::from_secs(connect_timeout))
+            .read_timeout(Duration::from_secs(read_timeout))
+            .user_agent(
+                self.user_agent
+                    .as_deref()
+                    .unwrap_or(OPENAI_DEFAULT_USER_AGENT),
+            );
+
+        if let Some(url) = &self.proxy {
+            let proxy =
+                Proxy::all(url).map_err(|err| OpenAiConfigError::Proxy(url.clone(), err))?;
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(path) = &self.ca_bundle {
+            let pem = std::fs::read(path)
+                .map_err(|err| OpenAiConfigError::CaBundleRead(path.clone(), err))?;
+            let certs = Certificate::from_pem_bundle(&pem)
+                .map_err(|err| OpenAiConfigError::CaBundleParse(path.clone(), err))?;
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        builder.build().map_err(OpenAiConfigError::Client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn env_wins_over_project_and_project_over_profile() {
+        let profile = OpenAiConfig {
+            base_url: Some("https://profile.example/v1".into()),
+            headers: headers(&[("X-Profile", "1"), ("X-Shared", "profile")]),
+            read_timeout: Some(30),
+            ..Default::default()
+        };
+        let project = OpenAiConfig {
+            base_url: Some("https://project.example/v1".into()),
+            headers: headers(&[("X-Project", "1"), ("X-Shared", "project")]),
+            ..Default::default()
+        };
+        let env = |name: &str| match name {
+            "OPENAI_BASE_URL" => Some("https://env.example/v1".to_string()),
+            OPENAI_HEADERS_ENV_VAR => Some("X-Env = 1,X-Shared=env\nmalformed".to_string()),
+            _ => None,
+        };
+
+        let config = profile.clone().merge(project.clone());
+        assert_eq!(config.base_url(), "https://project.example/v1");
+        assert_eq!(config.read_timeout, Some(30));
+        assert_eq!(config.headers["X-Shared"], "project");
+
+        let config = config.with_vars(env);
+        assert_eq!(config.base_url(), "https://env.example/v1");
+        assert_eq!(
+            config.headers,
+            headers(&[
+                ("X-Env", "1"),
+                ("X-Profile", "1"),
+                ("X-Project", "1"),
+                ("X-Shared", "env"),
+            ])
+        );
+    }
+
+    #[test]
+    fn prefers_xeval_base_url_env_var() {
+        let config = OpenAiConfig::default().with_vars(|name| match name {
+            "XEVAL_OPENAI_BASE_URL" => Some("https://xeval.example/v1/".to_string()),
+            "OPENAI_BASE_URL" => Some("https://openai.example/v1".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.base_url(), "https://xeval.example/v1");
+        assert_eq!(config.url("/evals"), "https://xeval.example/v1/evals");
+    }
+
+    #[test]
+    fn ignores_blank_env_vars() {
+        let config = OpenAiConfig {
+            base_url: Some("https://profile.example/v1".into()),
+            ..Default::default()
+        }
+        .with_vars(|_| Some(" ".to_string()));
+        assert_eq!(config.base_url(), "https://profile.example/v1");
+        assert!(config.headers.is_empty());
+    }
+}
@@ -21,6 +21,43 @@ pub struct OpenAiEval {
 #[literal("eval")]
 pub struct OpenAiEvalObject;
 
+impl OpenAiEval {
+    /// Types of graders and data source configs that didn't match a known
+    /// shape and were kept as raw JSON.
+    pub fn unknown_types(&self) -> Vec<String> {
+        let mut types = Vec::new();
+        if let OpenAiDataSourceConfig::Unknown(value) = &self.data_source_config {
+            types.push(raw_type_name(value));
+        }
+        for grader in &self.testing_criteria {
+            grader.collect_unknown_types(&mut types);
+        }
+        types
+    }
+}
+
+impl OpenAiGrader {
+    fn collect_unknown_types(&self, out: &mut Vec<String>) {
+        match self {
+            OpenAiGrader::Unknown(value) => out.push(raw_type_name(value)),
+            OpenAiGrader::Multi(multi) => {
+                for grader in multi.graders.values() {
+                    grader.collect_unknown_types(out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn raw_type_name(value: &Value) -> String {
+    value
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or("untyped")
+        .to_string()
+}
+
 //#region OpenAiDataSourceConfig
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
@@ -29,6 +66,8 @@ pub enum OpenAiDataSourceConfig {
     Custom(OpenAiCustomDataSourceConfig),
     Logs(OpenAiLogsDataSourceConfig),
     StoredCompletions(OpenAiStoredCompletionsDataSourceConfig),
+    /// Config type xeval doesn't know yet, kept as raw JSON.
+    Unknown(Value),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
@@ -75,6 +114,9 @@ pub enum OpenAiGrader {
     LabelModel(OpenAiGraderLabelModel),
     ScoreModel(OpenAiGraderScoreModel),
     Python(OpenAiGraderPython),
+    Multi(OpenAiGraderMulti),
+    /// Grader type xeval doesn't know yet, kept as raw JSON.
+    Unknown(Value),
 }
 
 //#region OpenAiGraderStringCheck
@@ -196,6 +238,22 @@ pub struct OpenAiGraderPythonType;
 
 //#endregion
 
+//#region OpenAiGraderMulti
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub struct OpenAiGraderMulti {
+    #[serde(rename = "type")]
+    pub r#type: OpenAiGraderMultiType,
+    pub name: String,
+    pub graders: BTreeMap<String, OpenAiGrader>,
+    pub calculate_output: String,
+}
+
+#[literal("multi")]
+pub struct OpenAiGraderMultiType;
+
+//#endregion
+
 //#region OpenAiModelInput
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
@@ -346,11 +404,7 @@ impl OpenAi {
         &self,
         params: &OpenAiListEvalsPageRequest,
     ) -> Result<OpenAiResponseList<OpenAiEval>, OpenAiEvalsError> {
-        let client = Client::new();
-        let mut req = client.get("https://api.openai.com/v1/evals").header(
-            header::AUTHORIZATION,
-            format!("Bearer {}", self.auth.token()),
-        );
+        let mut req = self.request(Method::GET, "evals");
 
         if let Some(project) = params.project.as_deref().filter(|p| !p.is_empty()) {
             req = req.header("OpenAI-Project", project);
@@ -363,8 +417,8 @@ impl OpenAi {
             order_by: params.order_by.as_deref(),
         });
 
-        let resp = req
-            .send()
+        let resp = self
+            .send(req)
             .await
             .map_err(OpenAiEvalsError::Request)?
             .error_for_status()
@@ -449,6 +503,7 @@ pub enum OpenAiDataSourceConfigUpsert {
     Custom(OpenAiCustomDataSourceConfigUpsert),
     Logs(OpenAiLogsDataSourceConfigUpsert),
     StoredCompletions(OpenAiStoredCompletionsDataSourceConfigUpsert),
+    Unknown(Value),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -483,18 +538,31 @@ impl OpenAi {
         project: Option<&str>,
         upsert: &OpenAiEvalUpsert,
     ) -> Result<OpenAiEval, OpenAiEvalsError> {
-        let client = Client::new();
-        let mut req = client
-            .post("https://api.openai.com/v1/evals")
-            .header(
-                header::AUTHORIZATION,
-                format!("Bearer {}", self.auth.token()),
-            )
-            .json(upsert);
+        let mut req = self.idempotent(self.request(Method::POST, "evals").json(upsert));
+        if let Some(project) = project.filter(|p| !p.is_empty()) {
+            req = req.header("OpenAI-Project", project);
+        }
+        let resp = self.send(req).await.map_err(OpenAiEvalsError::Request)?;
+        let status = resp.status();
+        let text = resp.text().await.map_err(OpenAiEvalsError::Body)?;
+        if !status.is_success() {
+            return Err(OpenAiEvalsError::Http(status.as_str().to_string(), text));
+        }
+        let eval =
+            serde_json::from_str::<OpenAiEval>(&text).map_err(OpenAiEvalsError::Deserialize)?;
+        Ok(eval)
+    }
+
+    pub async fn get_eval(
+        &self,
+        project: Option<&str>,
+        eval_id: &str,
+    ) -> Result<OpenAiEval, OpenAiEvalsError> {
+        let mut req = self.request(Method::GET, &format!("evals/{}", eval_id));
         if let Some(project) = project.filter(|p| !p.is_empty()) {
             req = req.header("OpenAI-Project", project);
         }
-        let resp = req.send().await.map_err(OpenAiEvalsError::Request)?;
+        let resp = self.send(req).await.map_err(OpenAiEvalsError::Request)?;
         let status = resp.status();
         let text = resp.text().await.map_err(OpenAiEvalsError::Body)?;
         if !status.is_success() {
@@ -523,18 +591,13 @@ impl OpenAi {
             );
         }
 
-        let client = Client::new();
-        let mut req = client
-            .post(format!("https://api.openai.com/v1/evals/{}", eval_id))
-            .header(
-                header::AUTHORIZATION,
-                format!("Bearer {}", self.auth.token()),
-            )
+        let mut req = self
+            .request(Method::POST, &format!("evals/{}", eval_id))
             .json(&body);
         if let Some(project) = project.filter(|p| !p.is_empty()) {
             req = req.header("OpenAI-Project", project);
         }
-        let resp = req.send().await.map_err(OpenAiEvalsError::Request)?;
+        let resp = self.send(req).await.map_err(OpenAiEvalsError::Request)?;
         let status = resp.status();
         let text = resp.text().await.map_err(OpenAiEvalsError::Body)?;
         if !status.is_success() {
@@ -544,4 +607,75 @@ impl OpenAi {
             serde_json::from_str::<OpenAiEval>(&text).map_err(OpenAiEvalsError::Deserialize)?;
         Ok(eval)
     }
+
+    pub async fn delete_eval(
+        &self,
+        project: Option<&str>,
+        eval_id: &str,
+    ) -> Result<OpenAiEvalDeleted, OpenAiEvalsError> {
+        let mut req = self.request(Method::DELETE, &format!("evals/{}", eval_id));
+        if let Some(project) = project.filter(|p| !p.is_empty()) {
+            req = req.header("OpenAI-Project", project);
+        }
+        let resp = self.send(req).await.map_err(OpenAiEvalsError::Request)?;
+        let status = resp.status();
+        let text = resp.text().await.map_err(OpenAiEvalsError::Body)?;
+        if !status.is_success() {
+            return Err(OpenAiEvalsError::Http(status.as_str().to_string(), text));
+        }
+        let deleted = serde_json::from_str::<OpenAiEvalDeleted>(&text)
+            .map_err(OpenAiEvalsError::Deserialize)?;
+        Ok(deleted)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalDeleted {
+    pub object: OpenAiEvalDeletedObject,
+    pub deleted: bool,
+    pub eval_id: String,
+}
+
+#[literal("eval.deleted")]
+pub struct OpenAiEvalDeletedObject;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::eval_value;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_unknown_graders_as_raw_json() {
+        let raw = eval_value(
+            "eval_123",
+            json!({"testing_criteria": [
+                {"type": "endpoint", "name": "Webhook grader", "url": "https://example.com"},
+            ]}),
+        );
+        let eval: OpenAiEval = serde_json::from_value(raw.clone()).unwrap();
+        assert_eq!(eval.unknown_types(), ["endpoint"]);
+        assert_eq!(
+            serde_json::to_value(&eval.testing_criteria).unwrap(),
+            raw["testing_criteria"]
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_data_source_configs_as_raw_json() {
+        let raw = eval_value(
+            "eval_123",
+            json!({"data_source_config": {"type": "webhook", "url": "https://example.com"}}),
+        );
+        let eval: OpenAiEval = serde_json::from_value(raw.clone()).unwrap();
+        assert!(matches!(
+            eval.data_source_config,
+            OpenAiDataSourceConfig::Unknown(_)
+        ));
+        assert_eq!(eval.unknown_types(), ["webhook"]);
+        assert_eq!(
+            serde_json::to_value(&eval.data_source_config).unwrap(),
+            raw["data_source_config"]
+        );
+    }
 }
@@ -0,0 +1,95 @@
+use crate::prelude::*;
+use litty::literal;
+use reqwest::header::CONTENT_TYPE;
+
+/// Purpose of files used as eval run data sources.
+pub const OPENAI_FILE_PURPOSE_EVALS: &str = "evals";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiFile {
+    pub object: OpenAiFileObject,
+    pub id: String,
+    pub bytes: u64,
+    pub created_at: i64,
+    pub filename: String,
+    pub purpose: String,
+}
+
+#[literal("file")]
+pub struct OpenAiFileObject;
+
+#[derive(Error, Debug)]
+pub enum OpenAiFilesError {
+    #[error("Failed to perform files request: {0}")]
+    Request(reqwest::Error),
+
+    #[error("Failed to obtain body text: {0}")]
+    Body(reqwest::Error),
+
+    #[error("Failed to parse files response: {0}")]
+    Deserialize(serde_json::Error),
+
+    #[error("HTTP {0}: {1}")]
+    Http(String, String),
+}
+
+impl OpenAi {
+    /// Upload a file through the files API. The form is built in memory so
+    /// the request can be retried like any other.
+    pub async fn upload_file(
+        &self,
+        project: Option<&str>,
+        filename: &str,
+        purpose: &str,
+        content: &[u8],
+    ) -> Result<OpenAiFile, OpenAiFilesError> {
+        let boundary = multipart_boundary(content);
+        let mut body = Vec::with_capacity(content.len() + 512);
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"purpose\"\r\n\r\n{purpose}\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                filename.replace(['"', '\r', '\n'], "_")
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(content);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        let mut req = self.idempotent(
+            self.request(Method::POST, "files")
+                .header(
+                    CONTENT_TYPE,
+                    format!("multipart/form-data; boundary={boundary}"),
+                )
+                .body(body),
+        );
+        if let Some(project) = project.filter(|p| !p.is_empty()) {
+            req = req.header("OpenAI-Project", project);
+        }
+        let resp = self.send(req).await.map_err(OpenAiFilesError::Request)?;
+        let status = resp.status();
+        let text = resp.text().await.map_err(OpenAiFilesError::Body)?;
+        if !status.is_success() {
+            return Err(OpenAiFilesError::Http(status.as_str().to_string(), text));
+        }
+        serde_json::from_str(&text).map_err(OpenAiFilesError::Deserialize)
+    }
+}
+
+/// Form boundary that doesn't occur in the content.
+fn multipart_boundary(content: &[u8]) -> String {
+    (0..)
+        .map(|n| format!("xeval-form-boundary-{n}"))
+        .find(|boundary| {
+            !content
+                .windows(boundary.len())
+                .any(|window| window == boundary.as_bytes())
+        })
+        .expect("content is finite")
+}
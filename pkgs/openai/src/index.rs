@@ -8,15 +8,22 @@ pub enum OpenAiError {
     #[error("Failed to verify token (server responded with {0} {1})")]
     InvalidToken(String, String),
 
+    #[error(transparent)]
+    Config(#[from] OpenAiConfigError),
+
+    #[error(transparent)]
+    Auth(#[from] OpenAiAuthError),
 }
 
 pub struct OpenAi {
     pub auth: OpenAiAuth,
+    pub config: OpenAiConfig,
+    client: Client,
 }
 
 impl OpenAi {
-    pub async fn new(auth: OpenAiAuth) -> Result<Option<Self>, OpenAiError> {
-        let openai = Self { auth };
+    pub async fn new(auth: OpenAiAuth, config: OpenAiConfig) -> Result<Option<Self>, OpenAiError> {
+        let openai = Self::unverified(auth, config)?;
         let verification = openai.verify().await?;
         match verification {
             OpenAiTokenVerification::Valid => Ok(Some(openai)),
@@ -26,30 +33,79 @@ impl OpenAi {
         }
     }
 
-    pub async fn from_token(token: OpenAiToken) -> Result<Option<Self>, OpenAiError> {
-        let auth = OpenAiAuth::new(token);
-        Self::new(auth).await
+    pub async fn from_token(
+        token: OpenAiToken,
+        config: OpenAiConfig,
+    ) -> Result<Option<Self>, OpenAiError> {
+        let auth = OpenAiAuth::new(token, OpenAiAuthSource::Prompt);
+        Self::new(auth, config).await
+    }
+
+    /// Detect a token without verifying it.
+    pub fn detect(
+        global: &Global,
+        profile: Option<&str>,
+        token: Option<OpenAiToken>,
+        config: OpenAiConfig,
+    ) -> Result<Option<Self>, OpenAiError> {
+        OpenAiAuth::detect(global, profile, token)?
+            .map(|auth| Self::unverified(auth, config))
+            .transpose()
     }
 
-    pub async fn detect(global: &Global) -> Result<Option<Self>, OpenAiError> {
-        let auth = OpenAiAuth::detect(global);
+    /// Build the HTTP client from the config without verifying the token.
+    pub fn unverified(auth: OpenAiAuth, config: OpenAiConfig) -> Result<Self, OpenAiError> {
+        Ok(Self {
+            auth,
+            client: config.client()?,
+            config,
+        })
+    }
+
+    /// Start an authorized request to an API path such as `evals`.
+    pub fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        let mut req = self
+            .auth
+            .authorize(self.client.request(method, self.config.url(path)));
+        for (name, value) in &self.config.headers {
+            req = req.header(name, value);
+        }
+        req
+    }
 
-        if let Some(auth) = auth {
-            Self::new(auth).await
+    /// Mark a create as safe to retry by giving it an idempotency key, if
+    /// the retry policy allows it.
+    pub fn idempotent(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if self.config.retry.policy().idempotency_keys {
+            req.header(OPENAI_IDEMPOTENCY_KEY_HEADER, idempotency_key())
         } else {
-            Ok(None)
+            req
+        }
+    }
+
+    /// Send a request, retrying rate limited and failed attempts according
+    /// to the retry policy.
+    pub async fn send(&self, req: reqwest::RequestBuilder) -> reqwest::Result<Response> {
+        let policy = self.config.retry.policy();
+        let request = req.build()?;
+        let mut attempt = 0;
+        loop {
+            // Streamed bodies can't be replayed
+            let Some(current) = request.try_clone() else {
+                return self.client.execute(request).await;
+            };
+            let outcome = self.client.execute(current).await;
+            match policy.retry_delay(&request, &outcome, attempt) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return outcome,
+            }
+            attempt += 1;
         }
     }
 
     pub async fn verify(&self) -> Result<OpenAiTokenVerification, OpenAiError> {
-        let client = Client::new();
-        let response = client
-            .get("https://api.openai.com/v1/models")
-            .header(
-                header::AUTHORIZATION,
-                format!("Bearer {}", self.auth.token()),
-            )
-            .send()
+        let response = self
+            .send(self.request(Method::GET, "models"))
             .await
             .map_err(|err| OpenAiError::VerificationRequest(err))?;
 
@@ -59,6 +115,18 @@ impl OpenAi {
             Ok(OpenAiTokenVerification::Invalid { response })
         }
     }
+
+    /// Like `verify` but fails with the server response if the token is rejected.
+    pub async fn check(&self) -> Result<(), OpenAiError> {
+        match self.verify().await? {
+            OpenAiTokenVerification::Valid => Ok(()),
+            OpenAiTokenVerification::Invalid { response } => {
+                let status = response.status().to_string();
+                let body = response.text().await.unwrap_or_default();
+                Err(OpenAiError::InvalidToken(status, body))
+            }
+        }
+    }
 }
 
 pub enum OpenAiTokenVerification {
@@ -1,7 +1,14 @@
 pub mod auth;
+pub mod config;
 pub mod index;
 pub mod prelude;
 pub mod token;
 pub mod projects;
 pub mod response;
+pub mod retry;
 pub mod evals;
+pub mod files;
+pub mod normalize;
+pub mod runs;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
@@ -0,0 +1,251 @@
+use crate::prelude::*;
+use serde_json::{Map, Number, Value};
+
+impl OpenAiEval {
+    /// Canonical JSON of the fields that define what an eval does. Two evals
+    /// with equal normalized values behave the same, regardless of whether
+    /// they were built locally or returned by the API.
+    ///
+    /// - Identity and bookkeeping fields (`id`, `created_at`, `metadata`) are dropped.
+    /// - Custom data source schemas are reduced to the item schema, because the
+    ///   sample schema is filled in by OpenAI when `include_sample_schema` is set.
+    /// - Object keys are sorted, numbers are compared as floats and null or
+    ///   empty values are treated as absent.
+    /// - `required` and `type` lists of the item schema are sorted.
+    pub fn normalized(&self) -> Value {
+        let mut eval = Map::new();
+        eval.insert("name".into(), Value::String(self.name.clone()));
+        eval.insert(
+            "data_source_config".into(),
+            self.data_source_config.normalized(),
+        );
+        eval.insert(
+            "testing_criteria".into(),
+            Value::Array(
+                self.testing_criteria
+                    .iter()
+                    .map(|grader| normalize_value(to_value(grader)))
+                    .collect(),
+            ),
+        );
+        normalize_value(Value::Object(eval))
+    }
+
+    /// Whether both evals normalize to the same content.
+    pub fn semantically_eq(&self, other: &OpenAiEval) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl OpenAiDataSourceConfig {
+    pub fn normalized(&self) -> Value {
+        match self {
+            OpenAiDataSourceConfig::Custom(config) => {
+                let (item_schema, include_sample_schema) = split_custom_schema(&config.schema);
+                normalize_value(serde_json::json!({
+                    "type": "custom",
+                    "item_schema": normalize_schema(item_schema),
+                    "include_sample_schema": include_sample_schema,
+                }))
+            }
+
+            _ => normalize_value(to_value(self)),
+        }
+    }
+}
+
+/// Remote and locally built custom schemas nest the item schema under
+/// `properties.item` next to `properties.sample`. Upserts send the bare item
+/// schema instead.
+fn split_custom_schema(schema: &Value) -> (Value, bool) {
+    let properties = schema.get("properties");
+    match properties.and_then(|p| p.get("item")) {
+        Some(item) => (
+            item.clone(),
+            properties.is_some_and(|p| p.get("sample").is_some()),
+        ),
+        None => (schema.clone(), false),
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// Sort the order-insensitive `required` and `type` lists of a JSON schema.
+/// Only keyword positions are touched, so a property that happens to be
+/// named `required` keeps its value as is.
+fn normalize_schema(schema: Value) -> Value {
+    let Value::Object(map) = schema else {
+        return normalize_value(schema);
+    };
+    let map = map
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match (key.as_str(), value) {
+                ("required" | "type", Value::Array(mut items)) => {
+                    items.sort_by_key(|item| item.to_string());
+                    Value::Array(items)
+                }
+                (
+                    "properties" | "patternProperties" | "$defs" | "definitions",
+                    Value::Object(schemas),
+                ) => Value::Object(
+                    schemas
+                        .into_iter()
+                        .map(|(name, schema)| (name, normalize_schema(schema)))
+                        .collect(),
+                ),
+                ("anyOf" | "oneOf" | "allOf" | "prefixItems", Value::Array(schemas)) => {
+                    Value::Array(schemas.into_iter().map(normalize_schema).collect())
+                }
+                ("items" | "additionalProperties" | "not", schema) => normalize_schema(schema),
+                (_, value) => value,
+            };
+            (key, value)
+        })
+        .collect();
+    normalize_value(Value::Object(map))
+}
+
+fn normalize_value(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map
+                .into_iter()
+                .map(|(key, value)| (key, normalize_value(value)))
+                .filter(|(_, value)| !is_empty(value))
+                .collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(entries.into_iter().collect())
+        }
+
+        Value::Array(items) => Value::Array(items.into_iter().map(normalize_value).collect()),
+
+        Value::Number(number) => number
+            .as_f64()
+            .and_then(Number::from_f64)
+            .map(Value::Number)
+            .unwrap_or(Value::Number(number)),
+
+        value => value,
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Object(map) => map.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use serde_json::json;
+
+    fn eval(item_required: Value, temperature: Value) -> Value {
+        testing::eval_value(
+            "local_eval_helpfulness",
+            json!({
+                "name": "helpfulness",
+                "created_at": 0,
+                "data_source_config": {
+                    "type": "custom",
+                    "schema": {
+                        "type": "object",
+                        "properties": {
+                            "item": {
+                                "type": "object",
+                                "properties": {
+                                    "question": {"type": "string"},
+                                    "answer": {"type": ["string", "null"]},
+                                },
+                                "required": item_required,
+                            },
+                            "sample": {
+                                "type": "object",
+                                "properties": {"output_text": {"type": "string"}},
+                                "required": ["output_text"],
+                            },
+                        },
+                        "required": ["item", "sample"],
+                    },
+                },
+                "testing_criteria": [{
+                    "type": "score_model",
+                    "name": "helpful",
+                    "model": "gpt-4o-mini",
+                    "pass_threshold": 0.5,
+                    "range": [0, 1],
+                    "sampling_params": {"temperature": temperature},
+                    "input": [{
+                        "type": "message",
+                        "role": "user",
+                        "content": "Q: {{item.question}} A: {{item.answer}}",
+                    }],
+                }],
+            }),
+        )
+    }
+
+    #[test]
+    fn local_eval_semantically_equals_remote_copy() {
+        let local: OpenAiEval =
+            serde_json::from_value(eval(json!(["answer", "question"]), json!(0))).unwrap();
+
+        // What the API returns: other id and metadata, reordered `required`,
+        // float sampling params and no `required` on the sample schema
+        let mut remote = eval(json!(["question", "answer"]), json!(0.0));
+        remote["id"] = json!("eval_123");
+        remote["created_at"] = json!(1757313550);
+        remote["metadata"] = json!({ "xeval_name": "helpfulness" });
+        remote["data_source_config"]["schema"]["properties"]["item"]["properties"]["answer"]["type"] =
+            json!(["null", "string"]);
+        remote["data_source_config"]["schema"]["properties"]["sample"]
+            .as_object_mut()
+            .unwrap()
+            .remove("required");
+        let remote: OpenAiEval = serde_json::from_value(remote).unwrap();
+
+        assert!(local.semantically_eq(&remote));
+        assert_ne!(
+            local.normalized(),
+            OpenAiEval {
+                name: "other".into(),
+                ..remote
+            }
+            .normalized()
+        );
+    }
+
+    #[test]
+    fn sorts_schema_keywords_only() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "required": {"type": "array", "items": {"type": ["string", "null"]}},
+                "type": {"enum": ["b", "a"]},
+            },
+            "required": ["type", "required"],
+        });
+        assert_eq!(
+            normalize_schema(schema),
+            json!({
+                "type": "object",
+                "properties": {
+                    "required": {"type": "array", "items": {"type": ["null", "string"]}},
+                    "type": {"enum": ["b", "a"]},
+                },
+                "required": ["required", "type"],
+            })
+        );
+
+        // Outside of schemas lists keep their order
+        let grader = json!({"type": "label_model", "labels": ["b", "a"], "required": ["b", "a"]});
+        assert_eq!(normalize_value(grader.clone()), grader);
+    }
+}
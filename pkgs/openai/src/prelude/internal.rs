@@ -1,7 +1,7 @@
 pub use std::path::PathBuf;
 
-pub use anyhow::{Context, Result, bail};
-pub use reqwest::{Client, Response, header};
+pub use anyhow::Result;
+pub use reqwest::{Client, Method, Response};
 pub use serde::{Deserialize, Serialize};
 pub use thiserror::Error;
 pub use toml_edit::*;
@@ -2,8 +2,12 @@ mod internal;
 pub(crate) use internal::*;
 
 pub use crate::auth::*;
+pub use crate::config::*;
 pub use crate::index::*;
 pub use crate::projects::*;
 pub use crate::response::*;
+pub use crate::retry::*;
 pub use crate::evals::*;
+pub use crate::files::*;
+pub use crate::runs::*;
 pub use crate::token::*;
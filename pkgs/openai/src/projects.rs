@@ -33,21 +33,16 @@ impl OpenAi {
         &self,
         params: &OpenAiListProjectsPageRequest,
     ) -> Result<OpenAiResponseList<OpenAiProject>, OpenAiProjectsError> {
-        let client = Client::new();
-        let req = client
-            .get("https://api.openai.com/v1/organization/projects")
-            .header(
-                header::AUTHORIZATION,
-                format!("Bearer {}", self.auth.token()),
-            )
+        let req = self
+            .request(Method::GET, "organization/projects")
             .query(&OpenAiProjectsQuery {
                 after: params.after.as_deref(),
                 include_archived: params.include_archived,
                 limit: params.limit,
             });
 
-        let resp = req
-            .send()
+        let resp = self
+            .send(req)
             .await
             .map_err(OpenAiProjectsError::Request)?
             .error_for_status()
@@ -0,0 +1,460 @@
+use crate::prelude::*;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const OPENAI_DEFAULT_MAX_RETRIES: u32 = 3;
+
+pub const OPENAI_DEFAULT_INITIAL_BACKOFF_MS: u64 = 500;
+
+pub const OPENAI_DEFAULT_MAX_BACKOFF_MS: u64 = 30_000;
+
+/// Header that makes a create safe to retry.
+pub const OPENAI_IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Upper bound for delays requested by the server, so a bogus header can't
+/// stall a command for hours.
+const OPENAI_MAX_SERVER_DELAY: Duration = Duration::from_secs(300);
+
+const GLOBAL_AUTH_OPENAI_RETRY_NAME: &str = "retry";
+
+const GLOBAL_AUTH_OPENAI_RETRY_MAX_RETRIES_NAME: &str = "max_retries";
+
+const GLOBAL_AUTH_OPENAI_RETRY_INITIAL_BACKOFF_NAME: &str = "initial_backoff_ms";
+
+const GLOBAL_AUTH_OPENAI_RETRY_MAX_BACKOFF_NAME: &str = "max_backoff_ms";
+
+const GLOBAL_AUTH_OPENAI_RETRY_IDEMPOTENCY_KEYS_NAME: &str = "idempotency_keys";
+
+/// Retry settings as written in configs. Unset settings use the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpenAiRetryConfig {
+    /// Retries after the first attempt, 0 disables retrying.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    /// Backoff before the first retry in milliseconds, doubled on each retry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_backoff_ms: Option<u64>,
+    /// Backoff cap in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_backoff_ms: Option<u64>,
+    /// Send an idempotency key with creates so they're retried on server
+    /// errors and timeouts too. Only safe if the API honors the key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_keys: Option<bool>,
+}
+
+impl OpenAiRetryConfig {
+    /// Settings stored in the `retry` table of an auth profile's `openai`
+    /// table.
+    pub fn from_table(table: &Table) -> Self {
+        let Some(retry) = table
+            .get(GLOBAL_AUTH_OPENAI_RETRY_NAME)
+            .and_then(|item| item.as_table_like())
+        else {
+            return Default::default();
+        };
+        let int_value = |key: &str| {
+            retry
+                .get(key)
+                .and_then(|item| item.as_integer())
+                .and_then(|value| u64::try_from(value).ok())
+        };
+        Self {
+            max_retries: int_value(GLOBAL_AUTH_OPENAI_RETRY_MAX_RETRIES_NAME)
+                .and_then(|value| u32::try_from(value).ok()),
+            initial_backoff_ms: int_value(GLOBAL_AUTH_OPENAI_RETRY_INITIAL_BACKOFF_NAME),
+            max_backoff_ms: int_value(GLOBAL_AUTH_OPENAI_RETRY_MAX_BACKOFF_NAME),
+            idempotency_keys: retry
+                .get(GLOBAL_AUTH_OPENAI_RETRY_IDEMPOTENCY_KEYS_NAME)
+                .and_then(|item| item.as_bool()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Layer `other` on top of these settings, its settings win.
+    pub fn merge(self, other: OpenAiRetryConfig) -> Self {
+        Self {
+            max_retries: other.max_retries.or(self.max_retries),
+            initial_backoff_ms: other.initial_backoff_ms.or(self.initial_backoff_ms),
+            max_backoff_ms: other.max_backoff_ms.or(self.max_backoff_ms),
+            idempotency_keys: other.idempotency_keys.or(self.idempotency_keys),
+        }
+    }
+
+    pub fn policy(&self) -> OpenAiRetryPolicy {
+        OpenAiRetryPolicy {
+            max_retries: self.max_retries.unwrap_or(OPENAI_DEFAULT_MAX_RETRIES),
+            initial_backoff: Duration::from_millis(
+                self.initial_backoff_ms
+                    .unwrap_or(OPENAI_DEFAULT_INITIAL_BACKOFF_MS),
+            ),
+            max_backoff: Duration::from_millis(
+                self.max_backoff_ms.unwrap_or(OPENAI_DEFAULT_MAX_BACKOFF_MS),
+            ),
+            idempotency_keys: self.idempotency_keys.unwrap_or(false),
+        }
+    }
+}
+
+/// When and how long to wait before retrying a failed request.
+///
+/// Rate limited requests (429) and connection failures never reached the
+/// API, so they're always retried. Server errors (5xx) and timeouts are
+/// only retried for idempotent methods and for requests that carry an
+/// idempotency key, since the API might have already applied them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenAiRetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub idempotency_keys: bool,
+}
+
+impl Default for OpenAiRetryPolicy {
+    fn default() -> Self {
+        OpenAiRetryConfig::default().policy()
+    }
+}
+
+impl OpenAiRetryPolicy {
+    /// Delay before retrying `request` after `attempt` (0-based) ended with
+    /// `outcome`, or `None` if it must not be retried.
+    pub fn retry_delay(
+        &self,
+        request: &reqwest::Request,
+        outcome: &reqwest::Result<Response>,
+        attempt: u32,
+    ) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+
+        let safe = Self::is_replayable(request);
+        match outcome {
+            Ok(response) => {
+                let status = response.status();
+                let retry = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                    || (status.is_server_error() && safe);
+                retry.then(|| {
+                    Self::server_delay(response.headers(), SystemTime::now())
+                        .unwrap_or_else(|| self.backoff(attempt))
+                })
+            }
+            Err(err) if err.is_connect() || (err.is_timeout() && safe) => {
+                Some(self.backoff(attempt))
+            }
+            Err(_) => None,
+        }
+    }
+
+    /// Exponential backoff with jitter: a random delay between half and the
+    /// full backoff, so concurrent requests don't retry in lockstep.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        let half = backoff / 2;
+        half + half.mul_f64(rand::random::<f64>())
+    }
+
+    /// Whether sending the request twice has the same effect as once.
+    fn is_replayable(request: &reqwest::Request) -> bool {
+        matches!(
+            *request.method(),
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+        ) || request
+            .headers()
+            .contains_key(OPENAI_IDEMPOTENCY_KEY_HEADER)
+    }
+
+    /// Delay asked for by `Retry-After` (seconds or an HTTP date),
+    /// `retry-after-ms` or the reset time of an exhausted `x-ratelimit-*`
+    /// budget.
+    fn server_delay(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+        let header = |name: &str| headers.get(name)?.to_str().ok().map(str::trim);
+
+        let retry_after = header("retry-after-ms")
+            .and_then(|ms| ms.parse::<f64>().ok())
+            .and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok())
+            .or_else(|| {
+                let value = header(RETRY_AFTER.as_str())?;
+                match value.parse::<f64>() {
+                    Ok(secs) => Duration::try_from_secs_f64(secs).ok(),
+                    // A date in the past means right away
+                    Err(_) => Some(
+                        parse_http_date(value)?
+                            .duration_since(now)
+                            .unwrap_or_default(),
+                    ),
+                }
+            });
+
+        let rate_limit_reset = ["requests", "tokens"]
+            .iter()
+            .filter(|budget| header(&format!("x-ratelimit-remaining-{budget}")) == Some("0"))
+            .filter_map(|budget| parse_reset(header(&format!("x-ratelimit-reset-{budget}"))?))
+            .max();
+
+        retry_after
+            .max(rate_limit_reset)
+            .map(|delay| delay.min(OPENAI_MAX_SERVER_DELAY))
+    }
+}
+
+/// Random key for `OPENAI_IDEMPOTENCY_KEY_HEADER`.
+pub fn idempotency_key() -> String {
+    format!("xeval-{:032x}", rand::random::<u128>())
+}
+
+/// Parse rate limit reset times such as `20ms`, `1s`, `1.5s` or `6m0s`.
+fn parse_reset(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let secs = match &rest[..unit_len] {
+            "ms" => number / 1000.0,
+            "s" | "" => number,
+            "m" => number * 60.0,
+            "h" => number * 3600.0,
+            _ => return None,
+        };
+        total += secs;
+        rest = &rest[unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+const HTTP_DATE_MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Parse an IMF-fixdate such as `Sun, 06 Nov 1994 08:49:37 GMT`, the only
+/// HTTP date format servers are allowed to send.
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let (_, date) = value.split_once(", ")?;
+    let [day, month, year, time, "GMT"] = date.split(' ').collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let day: u64 = day.parse().ok().filter(|day| (1..=31).contains(day))?;
+    let month = HTTP_DATE_MONTHS.iter().position(|name| *name == month)? as u64 + 1;
+    let year: u64 = year.parse().ok().filter(|year| *year >= 1970)?;
+    let [hours, minutes, seconds] = time
+        .split(':')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?[..]
+    else {
+        return None;
+    };
+    if hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+
+    // Days since the epoch in the proleptic Gregorian calendar, counting
+    // years from March so leap days come last
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86_400 + hours * 3600 + minutes * 60 + seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderName;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (HeaderName::from_static(name), value.parse().unwrap()))
+            .collect()
+    }
+
+    fn server_delay(pairs: &[(&'static str, &str)]) -> Option<Duration> {
+        OpenAiRetryPolicy::server_delay(&headers(pairs), SystemTime::now())
+    }
+
+    fn request(method: Method, headers: &[(&'static str, &str)]) -> reqwest::Request {
+        let mut request =
+            reqwest::Request::new(method, "http://localhost/v1/evals".parse().unwrap());
+        *request.headers_mut() = self::headers(headers);
+        request
+    }
+
+    fn response(status: u16) -> reqwest::Result<Response> {
+        Ok(http::Response::builder()
+            .status(status)
+            .body("")
+            .unwrap()
+            .into())
+    }
+
+    fn policy() -> OpenAiRetryPolicy {
+        OpenAiRetryConfig {
+            initial_backoff_ms: Some(100),
+            max_backoff_ms: Some(1000),
+            ..Default::default()
+        }
+        .policy()
+    }
+
+    #[test]
+    fn reads_retry_after_seconds() {
+        assert_eq!(
+            server_delay(&[("retry-after", "3")]),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(
+            server_delay(&[("retry-after", "1.5")]),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(server_delay(&[("retry-after", "soon")]), None);
+    }
+
+    #[test]
+    fn reads_retry_after_dates() {
+        let date = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(date));
+        assert_eq!(
+            parse_http_date("Thu, 29 Feb 2024 00:00:00 GMT"),
+            Some(UNIX_EPOCH + Duration::from_secs(1_709_164_800))
+        );
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 UTC"), None);
+
+        let headers = headers(&[("retry-after", "Sun, 06 Nov 1994 08:49:37 GMT")]);
+        let now = date - Duration::from_secs(120);
+        assert_eq!(
+            OpenAiRetryPolicy::server_delay(&headers, now),
+            Some(Duration::from_secs(120))
+        );
+        let now = date + Duration::from_secs(120);
+        assert_eq!(
+            OpenAiRetryPolicy::server_delay(&headers, now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn prefers_retry_after_ms() {
+        assert_eq!(
+            server_delay(&[("retry-after-ms", "250"), ("retry-after", "3")]),
+            Some(Duration::from_millis(250))
+        );
+    }
+
+    #[test]
+    fn reads_exhausted_rate_limit_resets() {
+        assert_eq!(
+            server_delay(&[
+                ("x-ratelimit-remaining-tokens", "0"),
+                ("x-ratelimit-reset-tokens", "250ms"),
+                ("x-ratelimit-remaining-requests", "10"),
+                ("x-ratelimit-reset-requests", "1s"),
+            ]),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            server_delay(&[
+                ("x-ratelimit-remaining-requests", "0"),
+                ("x-ratelimit-reset-requests", "1m30s"),
+                ("x-ratelimit-remaining-tokens", "0"),
+                ("x-ratelimit-reset-tokens", "250ms"),
+            ]),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(parse_reset("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_reset("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_reset("5x"), None);
+    }
+
+    #[test]
+    fn caps_server_delays() {
+        assert_eq!(
+            server_delay(&[
+                ("x-ratelimit-remaining-requests", "0"),
+                ("x-ratelimit-reset-requests", "6m0s"),
+            ]),
+            Some(OPENAI_MAX_SERVER_DELAY)
+        );
+        assert_eq!(
+            server_delay(&[("retry-after", "86400")]),
+            Some(OPENAI_MAX_SERVER_DELAY)
+        );
+    }
+
+    #[test]
+    fn jitters_and_caps_backoff() {
+        let policy = policy();
+        for (attempt, full) in [
+            (0, 100),
+            (1, 200),
+            (2, 400),
+            (3, 800),
+            (4, 1000),
+            (10, 1000),
+        ] {
+            let full = Duration::from_millis(full);
+            let delays: Vec<_> = (0..50).map(|_| policy.backoff(attempt)).collect();
+            assert!(
+                delays
+                    .iter()
+                    .all(|delay| *delay >= full / 2 && *delay <= full),
+                "attempt {attempt}: {delays:?}"
+            );
+            assert!(delays.iter().any(|delay| *delay != delays[0]));
+        }
+    }
+
+    #[test]
+    fn retries_rate_limits_for_any_method() {
+        let policy = policy();
+        let request = request(Method::POST, &[]);
+        let delay = policy.retry_delay(&request, &response(429), 0);
+        assert!(delay.is_some_and(|delay| delay <= Duration::from_millis(100)));
+        assert_eq!(policy.retry_delay(&request, &response(400), 0), None);
+        assert_eq!(policy.retry_delay(&request, &response(429), 3), None);
+    }
+
+    #[test]
+    fn retries_server_errors_only_when_replayable() {
+        let policy = policy();
+        assert!(!policy.idempotency_keys);
+        assert_eq!(
+            policy.retry_delay(&request(Method::POST, &[]), &response(500), 0),
+            None
+        );
+        assert!(
+            policy
+                .retry_delay(&request(Method::GET, &[]), &response(503), 0)
+                .is_some()
+        );
+        let keyed = request(Method::POST, &[("idempotency-key", &idempotency_key())]);
+        assert!(policy.retry_delay(&keyed, &response(502), 0).is_some());
+    }
+
+    #[test]
+    fn generates_unique_idempotency_keys() {
+        let key = idempotency_key();
+        assert_eq!(key.len(), "xeval-".len() + 32);
+        assert_ne!(key, idempotency_key());
+    }
+}
@@ -0,0 +1,510 @@
+use crate::prelude::*;
+use litty::literal;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+//#region OpenAiEvalRun
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRun {
+    pub object: OpenAiEvalRunObject,
+    pub id: String,
+    pub eval_id: String,
+    pub name: String,
+    pub status: OpenAiEvalRunStatus,
+    #[serde(default)]
+    pub model: Option<String>,
+    pub created_at: i64,
+    /// Echo of the run data source. Kept raw as responses may include
+    /// server-populated fields.
+    pub data_source: Value,
+    #[serde(default)]
+    pub error: Option<OpenAiEvalRunError>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    pub report_url: Option<String>,
+    pub result_counts: OpenAiEvalRunResultCounts,
+    /// `null` until the run has results.
+    #[serde(default)]
+    pub per_testing_criteria_results: Option<Vec<OpenAiEvalRunCriteriaResult>>,
+}
+
+#[literal("eval.run")]
+pub struct OpenAiEvalRunObject;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenAiEvalRunStatus {
+    Queued,
+    InProgress,
+    Completed,
+    Canceled,
+    Failed,
+}
+
+impl OpenAiEvalRunStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Canceled | Self::Failed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunError {
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenAiEvalRunResultCounts {
+    pub total: u64,
+    pub errored: u64,
+    pub failed: u64,
+    pub passed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunCriteriaResult {
+    pub testing_criteria: String,
+    pub passed: u64,
+    pub failed: u64,
+}
+
+//#endregion
+
+//#region OpenAiEvalRunDataSource
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OpenAiEvalRunDataSource {
+    Jsonl(OpenAiEvalRunJsonlDataSource),
+    Completions(OpenAiEvalRunCompletionsDataSource),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunJsonlDataSource {
+    #[serde(rename = "type")]
+    pub r#type: OpenAiEvalRunJsonlDataSourceType,
+    pub source: OpenAiEvalRunSource,
+}
+
+#[literal("jsonl")]
+pub struct OpenAiEvalRunJsonlDataSourceType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunCompletionsDataSource {
+    #[serde(rename = "type")]
+    pub r#type: OpenAiEvalRunCompletionsDataSourceType,
+    pub source: OpenAiEvalRunSource,
+    pub input_messages: OpenAiEvalRunInputMessages,
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub sampling_params: Map<String, Value>,
+}
+
+#[literal("completions")]
+pub struct OpenAiEvalRunCompletionsDataSourceType;
+
+//#region OpenAiEvalRunSource
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OpenAiEvalRunSource {
+    FileContent(OpenAiEvalRunFileContentSource),
+    FileId(OpenAiEvalRunFileIdSource),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunFileContentSource {
+    #[serde(rename = "type")]
+    pub r#type: OpenAiEvalRunFileContentSourceType,
+    pub content: Vec<OpenAiEvalRunContentItem>,
+}
+
+#[literal("file_content")]
+pub struct OpenAiEvalRunFileContentSourceType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunContentItem {
+    pub item: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<Map<String, Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunFileIdSource {
+    #[serde(rename = "type")]
+    pub r#type: OpenAiEvalRunFileIdSourceType,
+    pub id: String,
+}
+
+#[literal("file_id")]
+pub struct OpenAiEvalRunFileIdSourceType;
+
+//#endregion
+
+//#region OpenAiEvalRunInputMessages
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OpenAiEvalRunInputMessages {
+    Template(OpenAiEvalRunInputMessagesTemplate),
+    ItemReference(OpenAiEvalRunInputMessagesItemReference),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunInputMessagesTemplate {
+    #[serde(rename = "type")]
+    pub r#type: OpenAiEvalRunInputMessagesTemplateType,
+    pub template: Vec<OpenAiModelInput>,
+}
+
+#[literal("template")]
+pub struct OpenAiEvalRunInputMessagesTemplateType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunInputMessagesItemReference {
+    #[serde(rename = "type")]
+    pub r#type: OpenAiEvalRunInputMessagesItemReferenceType,
+    pub item_reference: String,
+}
+
+#[literal("item_reference")]
+pub struct OpenAiEvalRunInputMessagesItemReferenceType;
+
+//#endregion
+
+//#endregion
+
+//#region OpenAiEvalRunOutputItem
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunOutputItem {
+    pub object: OpenAiEvalRunOutputItemObject,
+    pub id: String,
+    pub run_id: String,
+    pub eval_id: String,
+    pub created_at: i64,
+    pub status: String,
+    pub datasource_item_id: i64,
+    pub datasource_item: Map<String, Value>,
+    #[serde(default)]
+    pub results: Vec<OpenAiEvalRunOutputItemResult>,
+    #[serde(default)]
+    pub sample: Value,
+}
+
+#[literal("eval.run.output_item")]
+pub struct OpenAiEvalRunOutputItemObject;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunOutputItemResult {
+    pub name: String,
+    pub passed: bool,
+    #[serde(default)]
+    pub score: Option<f64>,
+}
+
+//#endregion
+
+#[derive(Error, Debug)]
+pub enum OpenAiEvalRunsError {
+    #[error("Failed to perform eval runs request: {0}")]
+    Request(reqwest::Error),
+
+    #[error("Failed to obtain body text: {0}")]
+    Body(reqwest::Error),
+
+    #[error("Failed to parse eval runs response: {0}")]
+    Deserialize(serde_json::Error),
+
+    #[error("HTTP {0}: {1}")]
+    Http(String, String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEvalRunCreate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BTreeMap<String, String>>,
+    pub data_source: OpenAiEvalRunDataSource,
+}
+
+#[derive(Serialize)]
+struct OpenAiEvalRunsQuery<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    order: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<&'a str>,
+}
+
+impl OpenAi {
+    pub async fn create_eval_run(
+        &self,
+        project: Option<&str>,
+        eval_id: &str,
+        create: &OpenAiEvalRunCreate,
+    ) -> Result<OpenAiEvalRun, OpenAiEvalRunsError> {
+        let req = self.idempotent(
+            self.request(Method::POST, &format!("evals/{}/runs", eval_id))
+                .json(create),
+        );
+        self.send_eval_runs_request(req, project).await
+    }
+
+    pub async fn get_eval_run(
+        &self,
+        project: Option<&str>,
+        eval_id: &str,
+        run_id: &str,
+    ) -> Result<OpenAiEvalRun, OpenAiEvalRunsError> {
+        let req = self.request(Method::GET, &format!("evals/{}/runs/{}", eval_id, run_id));
+        self.send_eval_runs_request(req, project).await
+    }
+
+    pub async fn list_eval_runs_page(
+        &self,
+        eval_id: &str,
+        params: &OpenAiListEvalRunsPageRequest,
+    ) -> Result<OpenAiResponseList<OpenAiEvalRun>, OpenAiEvalRunsError> {
+        let req = self
+            .request(Method::GET, &format!("evals/{}/runs", eval_id))
+            .query(&OpenAiEvalRunsQuery {
+                after: params.after.as_deref(),
+                limit: params.limit,
+                order: params.order,
+                status: params.status,
+            });
+        self.send_eval_runs_request(req, params.project.as_deref())
+            .await
+    }
+
+    pub async fn cancel_eval_run(
+        &self,
+        project: Option<&str>,
+        eval_id: &str,
+        run_id: &str,
+    ) -> Result<OpenAiEvalRun, OpenAiEvalRunsError> {
+        let req = self.request(Method::POST, &format!("evals/{}/runs/{}", eval_id, run_id));
+        self.send_eval_runs_request(req, project).await
+    }
+
+    pub async fn list_eval_run_output_items_page(
+        &self,
+        eval_id: &str,
+        run_id: &str,
+        params: &OpenAiListEvalRunOutputItemsPageRequest,
+    ) -> Result<OpenAiResponseList<OpenAiEvalRunOutputItem>, OpenAiEvalRunsError> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("evals/{}/runs/{}/output_items", eval_id, run_id),
+            )
+            .query(&OpenAiEvalRunsQuery {
+                after: params.after.as_deref(),
+                limit: params.limit,
+                order: params.order,
+                status: params.status,
+            });
+        self.send_eval_runs_request(req, params.project.as_deref())
+            .await
+    }
+
+    pub async fn list_all_eval_run_output_items(
+        &self,
+        eval_id: &str,
+        run_id: &str,
+        params: &OpenAiListAllEvalRunOutputItemsRequest,
+    ) -> Result<Vec<OpenAiEvalRunOutputItem>, OpenAiEvalRunsError> {
+        let mut all: Vec<OpenAiEvalRunOutputItem> = Vec::new();
+        let mut after: Option<String> = None;
+
+        loop {
+            let page_params = OpenAiListEvalRunOutputItemsPageRequest {
+                project: params.project.clone(),
+                after: after.clone(),
+                limit: Some(100),
+                order: params.order,
+                status: params.status,
+            };
+
+            let page = self
+                .list_eval_run_output_items_page(eval_id, run_id, &page_params)
+                .await?;
+
+            let has_more = page.has_more;
+            if page.data.is_empty() {
+                break;
+            }
+
+            after = page.data.last().map(|i| i.id.clone());
+            all.extend(page.data);
+
+            if !has_more {
+                break;
+            }
+        }
+
+        Ok(all)
+    }
+
+    async fn send_eval_runs_request<T: DeserializeOwned>(
+        &self,
+        mut req: reqwest::RequestBuilder,
+        project: Option<&str>,
+    ) -> Result<T, OpenAiEvalRunsError> {
+        if let Some(project) = project.filter(|p| !p.is_empty()) {
+            req = req.header("OpenAI-Project", project);
+        }
+        let resp = self.send(req).await.map_err(OpenAiEvalRunsError::Request)?;
+        let status = resp.status();
+        let text = resp.text().await.map_err(OpenAiEvalRunsError::Body)?;
+        if !status.is_success() {
+            return Err(OpenAiEvalRunsError::Http(status.as_str().to_string(), text));
+        }
+        serde_json::from_str::<T>(&text).map_err(OpenAiEvalRunsError::Deserialize)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenAiListEvalRunsPageRequest {
+    pub project: Option<String>,
+    pub after: Option<String>,
+    pub limit: Option<u32>,
+    pub order: Option<&'static str>,
+    pub status: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenAiListEvalRunOutputItemsPageRequest {
+    pub project: Option<String>,
+    pub after: Option<String>,
+    pub limit: Option<u32>,
+    pub order: Option<&'static str>,
+    pub status: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenAiListAllEvalRunOutputItemsRequest {
+    pub project: Option<String>,
+    pub order: Option<&'static str>,
+    pub status: Option<&'static str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deserializes_canceled_run() {
+        let run: OpenAiEvalRun = serde_json::from_value(json!({
+            "object": "eval.run",
+            "id": "evalrun_67abd54d60ec8190832b46859da808f7",
+            "eval_id": "eval_67abd54d9b0081909a86353f6fb9317a",
+            "report_url": "https://platform.openai.com/evaluations/eval_67abd54d9b0081909a86353f6fb9317a?run_id=evalrun_67abd54d60ec8190832b46859da808f7",
+            "status": "canceled",
+            "model": "gpt-4o-mini",
+            "name": "gpt-4o-mini",
+            "created_at": 1743092069,
+            "result_counts": {"total": 0, "errored": 0, "failed": 0, "passed": 0},
+            "per_model_usage": null,
+            "per_testing_criteria_results": null,
+            "data_source": {
+                "type": "completions",
+                "source": {"type": "file_id", "id": "file-4ZvQ1tW7fY3xN8aB2cD5eR"},
+                "input_messages": {
+                    "type": "template",
+                    "template": [
+                        {
+                            "type": "message",
+                            "role": "user",
+                            "content": {"type": "input_text", "text": "{{item.input}}"}
+                        }
+                    ]
+                },
+                "model": "gpt-4o-mini",
+                "sampling_params": {"seed": 42, "temperature": 1.0, "top_p": 1.0}
+            },
+            "error": null,
+            "metadata": {}
+        }))
+        .unwrap();
+
+        assert_eq!(run.status, OpenAiEvalRunStatus::Canceled);
+        assert!(run.status.is_finished());
+        assert!(run.per_testing_criteria_results.is_none());
+        assert!(run.error.is_none());
+        assert_eq!(run.result_counts.total, 0);
+    }
+
+    #[test]
+    fn deserializes_output_items_page() {
+        let page: OpenAiResponseList<OpenAiEvalRunOutputItem> = serde_json::from_value(json!({
+            "object": "list",
+            "data": [
+                {
+                    "object": "eval.run.output_item",
+                    "id": "outputitem_67e5796c28e081909917bf79f6e6214d",
+                    "created_at": 1743092076,
+                    "run_id": "evalrun_67abd54d60ec8190832b46859da808f7",
+                    "eval_id": "eval_67abd54d9b0081909a86353f6fb9317a",
+                    "status": "pass",
+                    "datasource_item_id": 5,
+                    "datasource_item": {
+                        "input": "Stock Markets Rally After Positive Economic Data Released",
+                        "ground_truth": "Markets"
+                    },
+                    "results": [
+                        {
+                            "name": "String check-a2486074-d803-4445-b431-ad2262e85d47",
+                            "sample": null,
+                            "passed": true,
+                            "score": 1.0
+                        }
+                    ],
+                    "sample": {
+                        "input": [
+                            {"role": "user", "content": "Stock Markets Rally After Positive Economic Data Released"}
+                        ],
+                        "output": [{"role": "assistant", "content": "Markets"}],
+                        "finish_reason": "stop",
+                        "model": "gpt-4o-mini-2024-07-18",
+                        "usage": {
+                            "total_tokens": 325,
+                            "completion_tokens": 2,
+                            "prompt_tokens": 323,
+                            "cached_tokens": 0
+                        },
+                        "error": null,
+                        "temperature": 1.0,
+                        "max_completion_tokens": 2048,
+                        "top_p": 1.0,
+                        "seed": 42
+                    }
+                }
+            ],
+            "first_id": "outputitem_67e5796c28e081909917bf79f6e6214d",
+            "last_id": "outputitem_67e5796c28e081909917bf79f6e6214d",
+            "has_more": true
+        }))
+        .unwrap();
+
+        assert!(page.has_more);
+        let item = &page.data[0];
+        assert_eq!(item.datasource_item_id, 5);
+        assert_eq!(item.datasource_item["ground_truth"], "Markets");
+        assert_eq!(item.results.len(), 1);
+        assert!(item.results[0].passed);
+        assert_eq!(item.results[0].score, Some(1.0));
+        assert_eq!(item.sample["finish_reason"], "stop");
+    }
+}
@@ -0,0 +1,53 @@
+//! Eval fixtures shared by the tests of this and dependent crates. Enable
+//! the `testing` feature to use them outside of this crate.
+
+use crate::prelude::*;
+use serde_json::{Value, json};
+
+/// Eval JSON as the API returns it: a custom data source with an object item
+/// and no graders. Top-level `fields` replace the defaults.
+pub fn eval_value(id: &str, fields: Value) -> Value {
+    let mut eval = json!({
+        "object": "eval",
+        "id": id,
+        "name": id,
+        "created_at": 1,
+        "data_source_config": {
+            "type": "custom",
+            "schema": {
+                "type": "object",
+                "properties": {"item": {"type": "object"}},
+                "required": ["item"],
+            },
+        },
+        "testing_criteria": [],
+    });
+    if let (Some(eval), Value::Object(fields)) = (eval.as_object_mut(), fields) {
+        eval.extend(fields);
+    }
+    eval
+}
+
+/// Eval built from `eval_value`.
+pub fn eval(id: &str, fields: Value) -> OpenAiEval {
+    serde_json::from_value(eval_value(id, fields)).unwrap()
+}
+
+/// Eval created at `created_at` that was synced from the spec `name`.
+pub fn named_eval(id: &str, created_at: i64, name: &str) -> OpenAiEval {
+    eval(
+        id,
+        json!({"created_at": created_at, "metadata": {"xeval_name": name}}),
+    )
+}
+
+/// String check grader comparing the output text with `reference`.
+pub fn string_check_grader(name: &str, reference: &str) -> Value {
+    json!({
+        "type": "string_check",
+        "name": name,
+        "input": "{{sample.output_text}}",
+        "reference": reference,
+        "operation": "eq",
+    })
+}
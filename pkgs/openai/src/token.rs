@@ -8,6 +8,17 @@ impl OpenAiToken {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Token with everything but the prefix and the last 4 characters hidden.
+    pub fn masked(&self) -> String {
+        let chars: Vec<char> = self.0.chars().collect();
+        if chars.len() <= 12 {
+            return "*".repeat(chars.len());
+        }
+        let prefix: String = chars[..3].iter().collect();
+        let suffix: String = chars[chars.len() - 4..].iter().collect();
+        format!("{prefix}...{suffix}")
+    }
 }
 
 impl From<String> for OpenAiToken {
@@ -1,5 +1,7 @@
 use crate::prelude::*;
+use std::collections::BTreeMap;
 use std::fs;
+use toml_edit::{DocumentMut, Item, Table, value};
 
 pub const CONFIG_FILENAME: &str = "xeval.toml";
 
@@ -22,16 +24,98 @@ pub struct Config {
     /// Glob pattern to find eval YAML files
     #[serde(default = "Config::default_evals_glob")]
     pub evals: String,
+    /// Auth profile to use unless `--profile` or `XEVAL_PROFILE` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// OpenAI settings
+    #[serde(default, skip_serializing_if = "ConfigOpenAi::is_empty")]
+    pub openai: ConfigOpenAi,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ConfigOpenAi {
+    /// OpenAI project id to sync evals into. The default project if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// API base URL for OpenAI-compatible gateways or a local mock server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Extra headers sent with every request
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Connect timeout in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<u64>,
+    /// Timeout in seconds for each read of a response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_timeout: Option<u64>,
+    /// Proxy URL for all OpenAI requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// PEM file with extra root certificates, relative to the project
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<PathBuf>,
+    /// User agent sent with OpenAI requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Retrying of rate limited and failed OpenAI requests
+    #[serde(default, skip_serializing_if = "ConfigOpenAiRetry::is_empty")]
+    pub retry: ConfigOpenAiRetry,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ConfigOpenAiRetry {
+    /// Retries after the first attempt, 0 disables retrying
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    /// Backoff before the first retry in milliseconds, doubled on each retry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_backoff_ms: Option<u64>,
+    /// Backoff cap in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_backoff_ms: Option<u64>,
+    /// Send idempotency keys with creates so they're retried on server errors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_keys: Option<bool>,
+    /// Overrides for a command, e.g. `[openai.retry.commands.eval]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub commands: BTreeMap<String, ConfigOpenAiRetry>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             evals: Self::default_evals_glob(),
+            profile: None,
+            openai: Default::default(),
         }
     }
 }
 
+impl ConfigOpenAi {
+    fn is_empty(&self) -> bool {
+        self.project.is_none()
+            && self.base_url.is_none()
+            && self.headers.is_empty()
+            && self.connect_timeout.is_none()
+            && self.read_timeout.is_none()
+            && self.proxy.is_none()
+            && self.ca_bundle.is_none()
+            && self.user_agent.is_none()
+            && self.retry.is_empty()
+    }
+}
+
+impl ConfigOpenAiRetry {
+    fn is_empty(&self) -> bool {
+        self.max_retries.is_none()
+            && self.initial_backoff_ms.is_none()
+            && self.max_backoff_ms.is_none()
+            && self.idempotency_keys.is_none()
+            && self.commands.is_empty()
+    }
+}
+
 impl Config {
     pub fn resolve_path(path: &PathBuf) -> PathBuf {
         if path.ends_with(CONFIG_FILENAME) {
@@ -96,6 +180,36 @@ impl Config {
         Ok(())
     }
 
+    /// Sets `openai.project` in the project's config file, leaving the rest of
+    /// the file, including comments and formatting, untouched.
+    pub fn write_openai_project(&self, project: &Project) -> Result<(), ConfigError> {
+        let path = project.get_config_path();
+        let mut doc = fs::read_to_string(&path)
+            .map_err(|e| config::ConfigError::Message(format!("Failed to read config: {e}")))?
+            .parse::<DocumentMut>()
+            .map_err(|e| config::ConfigError::Message(format!("Failed to parse config: {e}")))?;
+        match &self.openai.project {
+            Some(id) => {
+                let openai = doc
+                    .entry("openai")
+                    .or_insert(Item::Table(Table::new()))
+                    .as_table_like_mut()
+                    .ok_or_else(|| {
+                        config::ConfigError::Message("Config openai is not a table".into())
+                    })?;
+                openai.insert("project", value(id.as_str()));
+            }
+            None => {
+                if let Some(openai) = doc.get_mut("openai").and_then(|o| o.as_table_like_mut()) {
+                    openai.remove("project");
+                }
+            }
+        }
+        fs::write(&path, doc.to_string())
+            .map_err(|e| config::ConfigError::Message(format!("Failed to write config: {e}")))?;
+        Ok(())
+    }
+
     pub fn write_new(path: &PathBuf, force: bool, config: &Config) -> Result<(), ConfigError> {
         let dest = Self::resolve_path(path);
         if dest.exists() && !force {
@@ -131,3 +245,38 @@ impl Project {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const CONFIG: &str = "# Evals of the math team\nevals = \"./specs/*.yaml\" # specs only\n\n[openai]\nbase_url = \"http://localhost:8080\"\n";
+
+    fn write_project(dir: &tempfile::TempDir, project: Option<&str>) -> String {
+        let config = Config {
+            // Resolved from `XEVAL_PROFILE`, must not end up in the file
+            profile: Some("work".into()),
+            openai: ConfigOpenAi {
+                project: project.map(Into::into),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let project = Project::from_config(dir.path().to_path_buf(), config.clone(), None);
+        config.write_openai_project(&project).unwrap();
+        fs::read_to_string(dir.path().join(CONFIG_FILENAME)).unwrap()
+    }
+
+    #[test]
+    fn write_openai_project_keeps_the_rest_of_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(CONFIG_FILENAME), CONFIG).unwrap();
+
+        assert_eq!(
+            write_project(&dir, Some("proj_1")),
+            format!("{CONFIG}project = \"proj_1\"\n")
+        );
+        assert_eq!(write_project(&dir, None), CONFIG);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod lock;
 pub mod prelude;
 pub mod project;
 pub mod state;
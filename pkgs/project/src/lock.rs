@@ -0,0 +1,190 @@
+use crate::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+
+pub const LOCK_FILENAME: &str = "xeval.lock";
+
+pub const LOCK_VERSION: u32 = 1;
+
+/// Lock key for evals synced without an explicit OpenAI project.
+pub const LOCK_DEFAULT_PROJECT: &str = "default";
+
+const LOCK_HEADER: &str = "# This file is maintained by xeval, commit it with your evals.\n\n";
+
+#[derive(Error, Debug)]
+pub enum LockError {
+    #[error("Failed to read lock file {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+
+    #[error("Failed to parse lock file {0}: {1}")]
+    Parse(PathBuf, toml::de::Error),
+
+    #[error("Unsupported lock file version {0}, update xeval")]
+    Version(u32),
+
+    #[error("Failed to serialize lock file: {0}")]
+    Serialize(toml::ser::Error),
+
+    #[error("Failed to write lock file {0}: {1}")]
+    Write(PathBuf, std::io::Error),
+}
+
+/// Maps spec names to the remote evals they were synced to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lock {
+    pub version: u32,
+    #[serde(default)]
+    pub evals: BTreeMap<String, LockEval>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockEval {
+    /// Spec path relative to the project root.
+    pub path: String,
+    /// Local content hash at the last sync.
+    pub hash: String,
+    /// Remote eval per OpenAI project.
+    #[serde(default)]
+    pub remote: BTreeMap<String, LockRemote>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockRemote {
+    pub id: String,
+    /// Content hash of the remote eval.
+    pub hash: String,
+}
+
+impl Default for Lock {
+    fn default() -> Self {
+        Self {
+            version: LOCK_VERSION,
+            evals: BTreeMap::new(),
+        }
+    }
+}
+
+impl Lock {
+    /// Read the project lock file or return an empty lock if it doesn't exist.
+    pub fn read(project: &Project) -> Result<Self, LockError> {
+        let path = project.get_lock_path();
+        if !path.exists() {
+            return Ok(Default::default());
+        }
+        let text = fs::read_to_string(&path).map_err(|e| LockError::Read(path.clone(), e))?;
+        let lock: Lock = toml::from_str(&text).map_err(|e| LockError::Parse(path, e))?;
+        if lock.version > LOCK_VERSION {
+            return Err(LockError::Version(lock.version));
+        }
+        Ok(lock)
+    }
+
+    pub fn write(&self, project: &Project) -> Result<(), LockError> {
+        let path = project.get_lock_path();
+        let toml = toml::to_string_pretty(self).map_err(LockError::Serialize)?;
+        fs::write(&path, format!("{LOCK_HEADER}{toml}")).map_err(|e| LockError::Write(path, e))
+    }
+
+    /// Lock key for the given OpenAI project id.
+    pub fn project_key(project: Option<&str>) -> &str {
+        project.unwrap_or(LOCK_DEFAULT_PROJECT)
+    }
+
+    pub fn remote(&self, name: &str, project_key: &str) -> Option<&LockRemote> {
+        self.evals.get(name)?.remote.get(project_key)
+    }
+
+    /// Record a synced spec. Returns whether the lock changed.
+    pub fn record(
+        &mut self,
+        name: &str,
+        path: &str,
+        hash: &str,
+        project_key: &str,
+        remote: LockRemote,
+    ) -> bool {
+        let entry = self
+            .evals
+            .entry(name.to_string())
+            .or_insert_with(|| LockEval {
+                path: path.to_string(),
+                hash: hash.to_string(),
+                remote: BTreeMap::new(),
+            });
+        let before = entry.clone();
+        entry.path = path.to_string();
+        entry.hash = hash.to_string();
+        entry.remote.insert(project_key.to_string(), remote);
+        *entry != before
+    }
+}
+
+impl Project {
+    /// The lock file lives next to the config file.
+    pub fn get_lock_path(&self) -> PathBuf {
+        self.config_path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.join(LOCK_FILENAME))
+            .unwrap_or_else(|| self.path.join(LOCK_FILENAME))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn project(dir: &tempfile::TempDir) -> Project {
+        Project::from_config(dir.path().to_path_buf(), Default::default(), None)
+    }
+
+    fn remote(id: &str) -> LockRemote {
+        LockRemote {
+            id: id.into(),
+            hash: format!("{id}-hash"),
+        }
+    }
+
+    #[test]
+    fn read_missing_lock_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(Lock::read(&project(&dir)).unwrap(), Lock::default());
+    }
+
+    #[test]
+    fn record_reports_changes() {
+        let mut lock = Lock::default();
+        assert!(lock.record("math", "evals/math.yaml", "a", "default", remote("eval_1")));
+        assert!(!lock.record("math", "evals/math.yaml", "a", "default", remote("eval_1")));
+        assert!(lock.record("math", "evals/math.yaml", "b", "default", remote("eval_2")));
+        assert!(lock.record("math", "evals/math.yaml", "b", "proj_1", remote("eval_3")));
+
+        assert_eq!(lock.remote("math", "default"), Some(&remote("eval_2")));
+        assert_eq!(lock.remote("math", "proj_1"), Some(&remote("eval_3")));
+        assert_eq!(lock.remote("math", "proj_2"), None);
+        assert_eq!(lock.remote("other", "default"), None);
+    }
+
+    #[test]
+    fn write_and_read_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project(&dir);
+        let mut lock = Lock::default();
+        lock.record("math", "evals/math.yaml", "a", "default", remote("eval_1"));
+        lock.record("tone", "evals/tone.yaml", "b", "proj_1", remote("eval_2"));
+        lock.write(&project).unwrap();
+
+        let text = fs::read_to_string(dir.path().join(LOCK_FILENAME)).unwrap();
+        assert!(text.starts_with(LOCK_HEADER));
+        assert_eq!(Lock::read(&project).unwrap(), lock);
+    }
+
+    #[test]
+    fn read_rejects_newer_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project(&dir);
+        fs::write(dir.path().join(LOCK_FILENAME), "version = 99\n").unwrap();
+        assert!(matches!(Lock::read(&project), Err(LockError::Version(99))));
+    }
+}
@@ -2,5 +2,6 @@ mod internal;
 pub(crate) use internal::*;
 
 pub use crate::config::*;
+pub use crate::lock::*;
 pub use crate::project::*;
 pub use crate::state::*;
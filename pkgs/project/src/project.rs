@@ -8,7 +8,7 @@ pub struct Project {
 }
 
 impl Project {
-    pub fn from_config(config: Config, config_path: Option<PathBuf>) -> Self {
+    pub fn from_config(path: PathBuf, config: Config, config_path: Option<PathBuf>) -> Self {
         Self {
             path,
             config,
@@ -1,9 +1,9 @@
 use crate::prelude::*;
+use serde::Serialize as SerSerialize;
+use serde::de::DeserializeOwned;
 use std::fs;
 use std::path::Path;
 use toml_edit::Document;
-use serde::de::DeserializeOwned;
-use serde::Serialize as SerSerialize;
 
 pub const PROJECT_STATE_DIRNAME: &str = ".xeval";
 
@@ -19,8 +19,9 @@ impl<'a> ProjectStateFile<'a> {
         let base = project.path.join(PROJECT_STATE_DIRNAME);
         let path = base.join(relative.as_ref());
         if let Some(parent) = Path::new(&path).parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create project state dir: {}", parent.display()))?;
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create project state dir: {}", parent.display())
+            })?;
         }
         Ok(Self { project, path })
     }
@@ -32,8 +33,9 @@ impl<'a> ProjectStateFile<'a> {
     {
         let s = doc.to_string();
         if let Some(parent) = Path::new(&self.path).parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create project state dir: {}", parent.display()))?;
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create project state dir: {}", parent.display())
+            })?;
         }
         fs::write(&self.path, s)
             .with_context(|| format!("Failed to write state file: {}", self.path.display()))?;
@@ -46,9 +48,8 @@ impl<'a> ProjectStateFile<'a> {
         T: DeserializeOwned + Default,
     {
         if Path::new(&self.path).exists() {
-            let text = fs::read_to_string(&self.path).with_context(|| {
-                format!("Failed to read state file: {}", self.path.display())
-            })?;
+            let text = fs::read_to_string(&self.path)
+                .with_context(|| format!("Failed to read state file: {}", self.path.display()))?;
             let value = toml::from_str::<T>(&text).with_context(|| {
                 format!("Failed to deserialize state file: {}", self.path.display())
             })?;
@@ -61,8 +62,9 @@ impl<'a> ProjectStateFile<'a> {
     /// Serialize `data` to TOML and write to the state file path.
     pub fn write_toml<T: SerSerialize>(&self, data: &T) -> Result<()> {
         if let Some(parent) = Path::new(&self.path).parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create project state dir: {}", parent.display()))?;
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create project state dir: {}", parent.display())
+            })?;
         }
         let s = toml::to_string_pretty(data).context("Failed to serialize TOML")?;
         fs::write(&self.path, s)
@@ -76,12 +78,10 @@ impl<'a> ProjectStateFile<'a> {
         T: DeserializeOwned + Default,
     {
         if Path::new(&self.path).exists() {
-            let text = fs::read_to_string(&self.path).with_context(|| {
-                format!("Failed to read state file: {}", self.path.display())
-            })?;
-            let value = serde_yaml::from_str::<T>(&text).with_context(|| {
-                format!("Failed to deserialize YAML: {}", self.path.display())
-            })?;
+            let text = fs::read_to_string(&self.path)
+                .with_context(|| format!("Failed to read state file: {}", self.path.display()))?;
+            let value = serde_yaml::from_str::<T>(&text)
+                .with_context(|| format!("Failed to deserialize YAML: {}", self.path.display()))?;
             Ok(value)
         } else {
             Ok(Default::default())
@@ -94,8 +94,9 @@ impl<'a> ProjectStateFile<'a> {
         T: SerSerialize,
     {
         if let Some(parent) = Path::new(&self.path).parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create project state dir: {}", parent.display()))?;
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create project state dir: {}", parent.display())
+            })?;
         }
         let s = serde_yaml::to_string(data).context("Failed to serialize YAML")?;
         fs::write(&self.path, s)
@@ -109,12 +110,10 @@ impl<'a> ProjectStateFile<'a> {
         T: DeserializeOwned + Default,
     {
         if Path::new(&self.path).exists() {
-            let text = fs::read_to_string(&self.path).with_context(|| {
-                format!("Failed to read state file: {}", self.path.display())
-            })?;
-            let value = serde_json::from_str::<T>(&text).with_context(|| {
-                format!("Failed to deserialize JSON: {}", self.path.display())
-            })?;
+            let text = fs::read_to_string(&self.path)
+                .with_context(|| format!("Failed to read state file: {}", self.path.display()))?;
+            let value = serde_json::from_str::<T>(&text)
+                .with_context(|| format!("Failed to deserialize JSON: {}", self.path.display()))?;
             Ok(value)
         } else {
             Ok(Default::default())
@@ -127,8 +126,9 @@ impl<'a> ProjectStateFile<'a> {
         T: SerSerialize,
     {
         if let Some(parent) = Path::new(&self.path).parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create project state dir: {}", parent.display()))?;
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create project state dir: {}", parent.display())
+            })?;
         }
         let s = serde_json::to_string_pretty(data).context("Failed to serialize JSON")?;
         fs::write(&self.path, s)
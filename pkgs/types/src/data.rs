@@ -0,0 +1,167 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::simple::{EvalSpec, SimpleField, SimpleFieldType};
+
+/// Single dataset item, keyed by field name.
+pub type DataRow = Map<String, Value>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataRowError {
+    /// 1-based row number.
+    pub row: usize,
+    /// Path to the offending value, e.g. `metadata.source` or `tags[1]`.
+    pub column: String,
+    pub message: String,
+}
+
+impl fmt::Display for DataRowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "row {}, column `{}`: {}",
+            self.row, self.column, self.message
+        )
+    }
+}
+
+impl EvalSpec {
+    /// Check a dataset row against the item fields. `row` is 1-based and only
+    /// used for error reporting.
+    pub fn validate_row(&self, row: usize, item: &DataRow) -> Vec<DataRowError> {
+        let mut errors = Vec::new();
+        check_fields(&self.item_fields(), item, "", &mut |column, message| {
+            errors.push(DataRowError {
+                row,
+                column,
+                message,
+            })
+        });
+        errors
+    }
+}
+
+fn check_fields(
+    fields: &BTreeMap<String, SimpleField>,
+    object: &Map<String, Value>,
+    prefix: &str,
+    report: &mut dyn FnMut(String, String),
+) {
+    for (key, field) in fields {
+        let path = format!("{prefix}{key}");
+        match object.get(key) {
+            Some(value) => check_value(field, value, &path, report),
+            None if field.optional => {}
+            None => report(path, "missing required value".into()),
+        }
+    }
+}
+
+fn check_value(
+    field: &SimpleField,
+    value: &Value,
+    path: &str,
+    report: &mut dyn FnMut(String, String),
+) {
+    if value.is_null() {
+        if !field.nullable {
+            report(path.into(), "value can't be null".into());
+        }
+        return;
+    }
+
+    match (&field.r#type, value) {
+        (SimpleFieldType::String, Value::String(s)) => {
+            if let Some(allowed) = &field.r#enum
+                && !allowed.contains(s)
+            {
+                report(
+                    path.into(),
+                    format!("expected one of {}, got {value}", allowed.join(", ")),
+                );
+            }
+        }
+
+        (SimpleFieldType::Number, Value::Number(_)) => {}
+
+        (SimpleFieldType::Boolean, Value::Bool(_)) => {}
+
+        (SimpleFieldType::Array, Value::Array(values)) => {
+            if let Some(items) = &field.items {
+                for (i, v) in values.iter().enumerate() {
+                    check_value(items, v, &format!("{path}[{i}]"), report);
+                }
+            }
+        }
+
+        (SimpleFieldType::Object, Value::Object(object)) => {
+            check_fields(&field.properties, object, &format!("{path}."), report);
+        }
+
+        (expected, _) => report(
+            path.into(),
+            format!("expected {}, got {value}", type_name(expected)),
+        ),
+    }
+}
+
+fn type_name(t: &SimpleFieldType) -> &'static str {
+    match t {
+        SimpleFieldType::String => "string",
+        SimpleFieldType::Number => "number",
+        SimpleFieldType::Boolean => "boolean",
+        SimpleFieldType::Array => "array",
+        SimpleFieldType::Object => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn reports_row_and_column_errors() {
+        let yaml = r#"
+name: chat
+schema:
+  a: number
+  tags:
+    type: array
+    items: string
+  meta:
+    type: object
+    optional: true
+    properties:
+      tone:
+        type: string
+        enum: [formal, casual]
+tests:
+  - type: string
+    input: "{{response.text}}"
+    eq: "{{answer}}"
+"#;
+        let spec: EvalSpec = serde_yaml::from_str(yaml).unwrap();
+
+        let ok = json!({"a": 1, "tags": ["x"], "answer": "1"});
+        assert_eq!(spec.validate_row(1, ok.as_object().unwrap()), vec![]);
+
+        let bad = json!({"a": "1", "tags": ["x", 2], "meta": {"tone": "rude"}});
+        let errors: Vec<String> = spec
+            .validate_row(2, bad.as_object().unwrap())
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "row 2, column `a`: expected number, got \"1\"",
+                "row 2, column `answer`: missing required value",
+                "row 2, column `meta.tone`: expected one of formal, casual, got \"rude\"",
+                "row 2, column `tags[1]`: expected string, got 2",
+            ]
+        );
+    }
+}
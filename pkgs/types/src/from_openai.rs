@@ -0,0 +1,380 @@
+use anyhow::{Context, Result, anyhow, bail};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::simple::{
+    EvalSpec, SimpleField, SimpleFieldType, SimpleMessage, SimpleMessageRole,
+    SimpleSimilarityMetric, TestSpec,
+};
+use crate::to_openai::slug_like;
+use xeval_openai::evals::*;
+
+impl EvalSpec {
+    /// Convert a remote eval back into a spec. Python grader sources end up in
+    /// `source_code`, with `source` pointing at `graders/<eval>-<grader>.py`
+    /// for the caller to write next to the spec.
+    pub fn from_openai_eval(eval: &OpenAiEval) -> Result<EvalSpec> {
+        let schema = match &eval.data_source_config {
+            OpenAiDataSourceConfig::Custom(config) => {
+                let item = config
+                    .schema
+                    .get("properties")
+                    .and_then(|p| p.get("item"))
+                    .unwrap_or(&config.schema);
+                object_fields(item).context("Unsupported item schema")?
+            }
+            _ => bail!("Only evals with a custom data source can be pulled"),
+        };
+
+        let mut sources = BTreeSet::new();
+        let tests = eval
+            .testing_criteria
+            .iter()
+            .map(|grader| grader_to_test(&eval.name, grader, &mut sources))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(EvalSpec {
+            name: eval.name.clone(),
+            schema,
+            tests,
+            data: None,
+            dataset: None,
+        })
+    }
+}
+
+/// Reverse of `translate_template_placeholders`:
+/// - {{sample.output_text}} -> {{response.text}}
+/// - {{item.foo}} -> {{foo}}
+pub fn untranslate_template_placeholders(s: &str) -> String {
+    static RE: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
+        Regex::new(r"\{\{\s*([a-zA-Z_][a-zA-Z0-9_]*(?:\.[a-zA-Z0-9_]+)?)\s*\}\}").expect("regex")
+    });
+
+    RE.replace_all(s, |caps: &regex::Captures| {
+        let path = &caps[1];
+        match path.strip_prefix("item.") {
+            _ if path == "sample.output_text" => "{{response.text}}".to_string(),
+            // Keep the prefix where the bare name would mean something else
+            Some(key) if !matches!(key, "sample" | "item" | "response") => {
+                format!("{{{{{}}}}}", key)
+            }
+            _ => format!("{{{{{}}}}}", path),
+        }
+    })
+    .into_owned()
+}
+
+fn object_fields(schema: &Value) -> Result<BTreeMap<String, SimpleField>> {
+    let required: BTreeSet<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let Some(properties) = schema.get("properties") else {
+        return Ok(BTreeMap::new());
+    };
+    let properties = properties
+        .as_object()
+        .ok_or_else(|| anyhow!("`properties` must be an object"))?;
+
+    properties
+        .iter()
+        .map(|(key, value)| {
+            let mut field =
+                json_schema_to_field(value).with_context(|| format!("Invalid field `{key}`"))?;
+            field.optional = !required.contains(key.as_str());
+            Ok((key.clone(), field))
+        })
+        .collect()
+}
+
+fn json_schema_to_field(schema: &Value) -> Result<SimpleField> {
+    let (type_name, nullable) = match schema.get("type") {
+        Some(Value::String(t)) => (t.as_str(), false),
+        Some(Value::Array(types)) => {
+            let names: Vec<&str> = types.iter().filter_map(Value::as_str).collect();
+            match names.as_slice() {
+                [t, "null"] | ["null", t] => (*t, true),
+                [t] => (*t, false),
+                _ => bail!("union types {names:?} aren't supported"),
+            }
+        }
+        _ => bail!("missing `type`"),
+    };
+
+    let r#type = match type_name {
+        "string" => SimpleFieldType::String,
+        "number" | "integer" => SimpleFieldType::Number,
+        "boolean" => SimpleFieldType::Boolean,
+        "array" => SimpleFieldType::Array,
+        "object" => SimpleFieldType::Object,
+        other => bail!("type `{other}` isn't supported"),
+    };
+
+    let r#enum = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter(|v| !v.is_null())
+                .map(|v| {
+                    v.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| anyhow!("only string enums are supported"))
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?;
+
+    let items = schema
+        .get("items")
+        .map(json_schema_to_field)
+        .transpose()
+        .context("Invalid `items`")?
+        .map(Box::new);
+
+    let properties = if r#type == SimpleFieldType::Object {
+        object_fields(schema)?
+    } else {
+        BTreeMap::new()
+    };
+
+    Ok(SimpleField {
+        r#type,
+        description: schema
+            .get("description")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        optional: false,
+        nullable,
+        r#enum,
+        items,
+        properties,
+    })
+}
+
+/// Drop grader names that match what `to_openai_eval` would default to.
+fn custom_name(name: &str, default: &str) -> Option<String> {
+    (name != default).then(|| name.to_string())
+}
+
+fn grader_to_test(
+    eval_name: &str,
+    grader: &OpenAiGrader,
+    sources: &mut BTreeSet<String>,
+) -> Result<TestSpec> {
+    match grader {
+        OpenAiGrader::StringCheck(g) => {
+            let reference = Some(untranslate_template_placeholders(&g.reference));
+            let (mut eq, mut ne, mut like, mut ilike) = (None, None, None, None);
+            match g.operation {
+                OpenAiGraderStringCheckOperation::Eq => eq = reference,
+                OpenAiGraderStringCheckOperation::Ne => ne = reference,
+                OpenAiGraderStringCheckOperation::Like => like = reference,
+                OpenAiGraderStringCheckOperation::Ilike => ilike = reference,
+            }
+            Ok(TestSpec::String {
+                name: custom_name(&g.name, "String check grader"),
+                input: untranslate_template_placeholders(&g.input),
+                eq,
+                ne,
+                like,
+                ilike,
+            })
+        }
+
+        OpenAiGrader::TextSimilarity(g) => Ok(TestSpec::Similarity {
+            name: custom_name(&g.name, "Text similarity grader"),
+            input: untranslate_template_placeholders(&g.input),
+            reference: untranslate_template_placeholders(&g.reference),
+            metric: similarity_metric_from_openai(&g.evaluation_metric),
+            pass_threshold: g.pass_threshold,
+        }),
+
+        OpenAiGrader::ScoreModel(g) => Ok(TestSpec::Score {
+            name: custom_name(&g.name, "Score model grader"),
+            model: g.model.clone(),
+            range: g.range,
+            pass_threshold: g.pass_threshold,
+            sampling_params: g.sampling_params.clone(),
+            messages: model_inputs_to_messages(&g.input)?,
+        }),
+
+        OpenAiGrader::LabelModel(g) => Ok(TestSpec::Label {
+            name: custom_name(&g.name, "Label model grader"),
+            model: g.model.clone(),
+            labels: g.labels.clone(),
+            passing_labels: g.passing_labels.clone(),
+            messages: model_inputs_to_messages(&g.input)?,
+        }),
+
+        OpenAiGrader::Python(g) => {
+            let base = format!("graders/{}-{}", slug_like(eval_name), slug_like(&g.name));
+            let mut source = format!("{base}.py");
+            let mut n = 2;
+            while !sources.insert(source.clone()) {
+                source = format!("{base}-{n}.py");
+                n += 1;
+            }
+            Ok(TestSpec::Python {
+                name: custom_name(&g.name, "Python grader"),
+                source,
+                image_tag: g.image_tag.clone(),
+                pass_threshold: g.pass_threshold,
+                source_code: Some(g.source.clone()),
+            })
+        }
+
+        OpenAiGrader::Multi(g) => Ok(TestSpec::Multi {
+            name: g.name.clone(),
+            calculate_output: g.calculate_output.clone(),
+            tests: g
+                .graders
+                .iter()
+                .map(|(key, grader)| Ok((key.clone(), grader_to_test(eval_name, grader, sources)?)))
+                .collect::<Result<BTreeMap<_, _>>>()?,
+        }),
+
+        OpenAiGrader::Unknown(value) => bail!(
+            "grader type `{}` isn't supported",
+            value
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or("untyped")
+        ),
+    }
+}
+
+fn model_inputs_to_messages(inputs: &[OpenAiModelInput]) -> Result<Vec<SimpleMessage>> {
+    inputs
+        .iter()
+        .map(|input| {
+            let OpenAiModelInputContent::String(content) = &input.content else {
+                bail!("only plain text grader messages are supported");
+            };
+            let role = match input.role {
+                OpenAiMessageRole::User => SimpleMessageRole::User,
+                OpenAiMessageRole::Assistant => SimpleMessageRole::Assistant,
+                OpenAiMessageRole::System => SimpleMessageRole::System,
+                OpenAiMessageRole::Developer => SimpleMessageRole::Developer,
+            };
+            Ok(SimpleMessage {
+                role,
+                content: untranslate_template_placeholders(content),
+            })
+        })
+        .collect()
+}
+
+fn similarity_metric_from_openai(
+    metric: &OpenAiGraderTextSimilarityEvaluationMetric,
+) -> SimpleSimilarityMetric {
+    match metric {
+        OpenAiGraderTextSimilarityEvaluationMetric::Cosine => SimpleSimilarityMetric::Cosine,
+        OpenAiGraderTextSimilarityEvaluationMetric::FuzzyMatch => {
+            SimpleSimilarityMetric::FuzzyMatch
+        }
+        OpenAiGraderTextSimilarityEvaluationMetric::Bleu => SimpleSimilarityMetric::Bleu,
+        OpenAiGraderTextSimilarityEvaluationMetric::Gleu => SimpleSimilarityMetric::Gleu,
+        OpenAiGraderTextSimilarityEvaluationMetric::Meteor => SimpleSimilarityMetric::Meteor,
+        OpenAiGraderTextSimilarityEvaluationMetric::Rouge1 => SimpleSimilarityMetric::Rouge1,
+        OpenAiGraderTextSimilarityEvaluationMetric::Rouge2 => SimpleSimilarityMetric::Rouge2,
+        OpenAiGraderTextSimilarityEvaluationMetric::Rouge3 => SimpleSimilarityMetric::Rouge3,
+        OpenAiGraderTextSimilarityEvaluationMetric::Rouge4 => SimpleSimilarityMetric::Rouge4,
+        OpenAiGraderTextSimilarityEvaluationMetric::Rouge5 => SimpleSimilarityMetric::Rouge5,
+        OpenAiGraderTextSimilarityEvaluationMetric::RougeL => SimpleSimilarityMetric::RougeL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untranslates_template_placeholders() {
+        assert_eq!(
+            untranslate_template_placeholders(
+                "{{ sample.output_text }} vs {{item.answer}} in {{item.item}} {{sample.model}}"
+            ),
+            "{{response.text}} vs {{answer}} in {{item.item}} {{sample.model}}"
+        );
+    }
+
+    #[test]
+    fn round_trips_spec_through_openai_eval() {
+        let yaml = r#"
+name: support
+schema:
+  question:
+    type: string
+    description: Customer question
+  tier:
+    type: string
+    enum: [free, pro]
+    nullable: true
+  tags:
+    type: array
+    items: string
+    optional: true
+  answer: string
+tests:
+  - type: string
+    input: "{{response.text}}"
+    ilike: "%{{answer}}%"
+  - type: similarity
+    name: Close to answer
+    input: "{{response.text}}"
+    reference: "{{answer}}"
+    metric: fuzzy_match
+    pass_threshold: 0.8
+  - type: score
+    model: gpt-4o-mini
+    range: [1, 5]
+    pass_threshold: 4
+    messages:
+      - role: system
+        content: Rate the answer from 1 to 5.
+      - role: user
+        content: "{{question}} -> {{response.text}}"
+  - type: multi
+    name: Tone
+    calculate_output: "polite"
+    tests:
+      polite:
+        type: label
+        model: gpt-4o-mini
+        labels: [polite, rude]
+        passing_labels: [polite]
+        messages:
+          - role: user
+            content: "{{response.text}}"
+"#;
+        let spec: EvalSpec = serde_yaml::from_str(yaml).unwrap();
+        let eval = spec.to_openai_eval().unwrap();
+        let pulled = EvalSpec::from_openai_eval(&eval).unwrap();
+        assert_eq!(pulled, spec);
+    }
+
+    #[test]
+    fn rejects_unknown_graders() {
+        let eval: OpenAiEval = serde_json::from_str(
+            r#"{
+            "object": "eval",
+            "id": "eval_123",
+            "name": "webhook",
+            "created_at": 1757313550,
+            "data_source_config": { "type": "custom", "schema": {} },
+            "testing_criteria": [
+                { "type": "endpoint", "name": "Webhook grader", "url": "https://example.com" }
+            ]
+        }"#,
+        )
+        .unwrap();
+        let err = EvalSpec::from_openai_eval(&eval).unwrap_err();
+        assert_eq!(err.to_string(), "grader type `endpoint` isn't supported");
+    }
+}
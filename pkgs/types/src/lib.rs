@@ -1,4 +1,10 @@
+pub mod data;
+pub mod from_openai;
 pub mod simple;
 pub mod to_openai;
 
-pub use simple::{EvalSpec, SimpleFieldType, TestSpec};
+pub use data::{DataRow, DataRowError};
+pub use simple::{
+    EvalSpec, SimpleField, SimpleFieldType, SimpleMessage, SimpleMessageRole,
+    SimpleSimilarityMetric, TestSpec,
+};
@@ -1,12 +1,53 @@
-use serde::{Deserialize, Serialize};
+use anyhow::{Result, bail};
+use ordered_float::OrderedFloat;
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
 use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::data::DataRow;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct EvalSpec {
     pub name: String,
     #[serde(default, alias = "input")]
-    pub schema: BTreeMap<String, SimpleFieldType>,
+    pub schema: BTreeMap<String, SimpleField>,
     pub tests: Vec<TestSpec>,
+    /// Inline dataset rows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<DataRow>>,
+    /// Path to a `.jsonl` or `.csv` dataset, relative to the spec file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataset: Option<String>,
+}
+
+/// Declared dataset field. Written either as a bare type (`a: number`) or as
+/// a table with extra constraints:
+///
+/// ```yaml
+/// tags:
+///   type: array
+///   items: string
+/// status:
+///   type: string
+///   enum: [open, closed]
+///   optional: true
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleField {
+    pub r#type: SimpleFieldType,
+    pub description: Option<String>,
+    /// The field may be omitted from the item.
+    pub optional: bool,
+    /// The field may be `null`.
+    pub nullable: bool,
+    /// Allowed values, only for `string` fields.
+    pub r#enum: Option<Vec<String>>,
+    /// Element type, required for `array` fields.
+    pub items: Option<Box<SimpleField>>,
+    /// Nested fields, only for `object` fields.
+    pub properties: BTreeMap<String, SimpleField>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -15,24 +56,343 @@ pub enum SimpleFieldType {
     String,
     Number,
     Boolean,
+    Array,
+    Object,
+}
+
+impl From<SimpleFieldType> for SimpleField {
+    fn from(r#type: SimpleFieldType) -> Self {
+        Self {
+            r#type,
+            description: None,
+            optional: false,
+            nullable: false,
+            r#enum: None,
+            items: None,
+            properties: BTreeMap::new(),
+        }
+    }
+}
+
+impl SimpleField {
+    fn is_shorthand(&self) -> bool {
+        *self == SimpleField::from(self.r#type.clone())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.r#enum.is_some() && self.r#type != SimpleFieldType::String {
+            return Err("`enum` is only allowed on string fields".into());
+        }
+        match self.r#type {
+            SimpleFieldType::Array if self.items.is_none() => {
+                Err("array fields require `items`".into())
+            }
+            SimpleFieldType::Array => Ok(()),
+            _ if self.items.is_some() => Err("`items` is only allowed on array fields".into()),
+            SimpleFieldType::Object => Ok(()),
+            _ if !self.properties.is_empty() => {
+                Err("`properties` is only allowed on object fields".into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SimpleFieldTable {
+    #[serde(rename = "type")]
+    r#type: SimpleFieldType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    optional: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    nullable: bool,
+    #[serde(default, rename = "enum", skip_serializing_if = "Option::is_none")]
+    r#enum: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    items: Option<Box<SimpleField>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    properties: BTreeMap<String, SimpleField>,
+}
+
+impl Serialize for SimpleField {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_shorthand() {
+            return self.r#type.serialize(serializer);
+        }
+        SimpleFieldTable {
+            r#type: self.r#type.clone(),
+            description: self.description.clone(),
+            optional: self.optional,
+            nullable: self.nullable,
+            r#enum: self.r#enum.clone(),
+            items: self.items.clone(),
+            properties: self.properties.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SimpleField {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SimpleFieldVisitor;
+
+        impl<'de> Visitor<'de> for SimpleFieldVisitor {
+            type Value = SimpleField;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a field type name or a field table")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<SimpleField, E> {
+                let r#type = SimpleFieldType::deserialize(de::value::StrDeserializer::new(v))?;
+                Ok(r#type.into())
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<SimpleField, A::Error> {
+                let table =
+                    SimpleFieldTable::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                let field = SimpleField {
+                    r#type: table.r#type,
+                    description: table.description,
+                    optional: table.optional,
+                    nullable: table.nullable,
+                    r#enum: table.r#enum,
+                    items: table.items,
+                    properties: table.properties,
+                };
+                field.validate().map_err(de::Error::custom)?;
+                Ok(field)
+            }
+        }
+
+        deserializer.deserialize_any(SimpleFieldVisitor)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum TestSpec {
     String {
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         name: Option<String>,
         input: String,
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         eq: Option<String>,
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         ne: Option<String>,
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         like: Option<String>,
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         ilike: Option<String>,
     },
+    Similarity {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        input: String,
+        reference: String,
+        metric: SimpleSimilarityMetric,
+        pass_threshold: OrderedFloat<f64>,
+    },
+    Score {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        model: String,
+        #[serde(default = "default_score_range")]
+        range: [OrderedFloat<f64>; 2],
+        pass_threshold: OrderedFloat<f64>,
+        #[serde(default, skip_serializing_if = "Map::is_empty")]
+        sampling_params: Map<String, Value>,
+        messages: Vec<SimpleMessage>,
+    },
+    Label {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        model: String,
+        labels: Vec<String>,
+        passing_labels: Vec<String>,
+        messages: Vec<SimpleMessage>,
+    },
+    Python {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        /// Path to the grader `.py` file, relative to the spec file.
+        source: String,
+        image_tag: String,
+        pass_threshold: OrderedFloat<f64>,
+        /// Contents of `source`, inlined by the loader.
+        #[serde(skip)]
+        source_code: Option<String>,
+    },
+    Multi {
+        name: String,
+        /// Formula combining child test scores by their keys, e.g.
+        /// `0.7 * correctness + 0.3 * style`.
+        calculate_output: String,
+        tests: BTreeMap<String, TestSpec>,
+    },
+}
+
+fn default_score_range() -> [OrderedFloat<f64>; 2] {
+    [OrderedFloat(0.0), OrderedFloat(1.0)]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SimpleMessage {
+    pub role: SimpleMessageRole,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SimpleMessageRole {
+    User,
+    Assistant,
+    System,
+    Developer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SimpleSimilarityMetric {
+    Cosine,
+    FuzzyMatch,
+    Bleu,
+    Gleu,
+    Meteor,
+    #[serde(rename = "rouge_1")]
+    Rouge1,
+    #[serde(rename = "rouge_2")]
+    Rouge2,
+    #[serde(rename = "rouge_3")]
+    Rouge3,
+    #[serde(rename = "rouge_4")]
+    Rouge4,
+    #[serde(rename = "rouge_5")]
+    Rouge5,
+    RougeL,
+}
+
+/// Functions allowed in multi test `calculate_output` formulas.
+const MULTI_FORMULA_FUNCTIONS: &[&str] =
+    &["min", "max", "abs", "floor", "ceil", "exp", "sqrt", "log"];
+
+impl EvalSpec {
+    /// Check constraints that can't be expressed through the serde shape alone.
+    pub fn validate(&self) -> Result<()> {
+        if self.data.is_some() && self.dataset.is_some() {
+            bail!(
+                "{} declares both inline `data` and a `dataset` file",
+                self.name
+            );
+        }
+        for (index, test) in self.tests.iter().enumerate() {
+            test.validate().map_err(|err| {
+                err.context(format!("Invalid test #{} in {}", index + 1, self.name))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl TestSpec {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            TestSpec::Label {
+                labels,
+                passing_labels,
+                ..
+            } => {
+                if labels.is_empty() {
+                    bail!("label test requires at least one label");
+                }
+                if passing_labels.is_empty() {
+                    bail!("label test requires at least one passing label");
+                }
+                for label in passing_labels {
+                    if !labels.contains(label) {
+                        bail!("passing label '{label}' is not listed in labels");
+                    }
+                }
+                Ok(())
+            }
+
+            TestSpec::Score {
+                range,
+                pass_threshold,
+                ..
+            } => {
+                if range[0] >= range[1] {
+                    bail!(
+                        "score test range must be ascending, got [{}, {}]",
+                        range[0],
+                        range[1]
+                    );
+                }
+                if *pass_threshold < range[0] || *pass_threshold > range[1] {
+                    bail!(
+                        "score test pass_threshold {} is outside of range [{}, {}]",
+                        pass_threshold,
+                        range[0],
+                        range[1]
+                    );
+                }
+
+                Ok(())
+            }
+
+            TestSpec::Multi {
+                calculate_output,
+                tests,
+                ..
+            } => {
+                if tests.is_empty() {
+                    bail!("multi test requires at least one child test");
+                }
+                for (key, test) in tests {
+                    if matches!(test, TestSpec::Multi { .. }) {
+                        bail!("multi test '{key}' cannot contain nested multi tests");
+                    }
+                    test.validate()
+                        .map_err(|err| err.context(format!("Invalid child test '{key}'")))?;
+                }
+                for ident in formula_identifiers(calculate_output) {
+                    if !tests.contains_key(ident) && !MULTI_FORMULA_FUNCTIONS.contains(&ident) {
+                        bail!("calculate_output references unknown test '{ident}'");
+                    }
+                }
+                Ok(())
+            }
+
+            _ => Ok(()),
+        }
+    }
+}
+
+fn formula_identifiers(formula: &str) -> Vec<&str> {
+    let bytes = formula.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c.is_ascii_alphabetic() || c == b'_' || c.is_ascii_digit() {
+            let start = i;
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'.')
+            {
+                i += 1;
+            }
+            // Numeric literals (incl. 1e-3 style exponents) aren't identifiers
+            if !c.is_ascii_digit() {
+                out.push(&formula[start..i]);
+            }
+        } else {
+            i += 1;
+        }
+    }
+    out
 }
 
 #[cfg(test)]
@@ -69,4 +429,108 @@ tests:
         let spec2: EvalSpec = serde_yaml::from_str(yaml2).expect("parse yaml2");
         assert_yaml_snapshot!("spec_from_schema", &spec2);
     }
+
+    #[test]
+    fn parse_rich_schema_fields() {
+        let yaml = r#"
+name: chat
+schema:
+  question: string
+  messages:
+    type: array
+    items:
+      type: object
+      properties:
+        role:
+          type: string
+          enum: [user, assistant]
+        content: string
+  metadata:
+    type: object
+    optional: true
+    description: Free-form request metadata
+    properties:
+      source:
+        type: string
+        nullable: true
+tests:
+  - type: string
+    input: "{{response.text}}"
+    eq: "{{answer}}"
+"#;
+        let spec: EvalSpec = serde_yaml::from_str(yaml).expect("parse yaml");
+        assert_yaml_snapshot!("spec_with_rich_schema", &spec.schema);
+
+        let yaml2 = r#"
+name: chat
+schema:
+  tags:
+    type: array
+tests: []
+"#;
+        let err = serde_yaml::from_str::<EvalSpec>(yaml2).expect_err("items are required");
+        assert!(err.to_string().contains("array fields require `items`"));
+    }
+
+    #[test]
+    fn validate_label_test_labels() {
+        let yaml = r#"
+name: tone
+tests:
+  - type: label
+    model: gpt-4o-mini
+    labels: [polite, neutral, rude]
+    passing_labels: [polite, friendly]
+    messages:
+      - role: user
+        content: "Classify the tone: {{response.text}}"
+"#;
+        let spec: EvalSpec = serde_yaml::from_str(yaml).expect("parse yaml");
+        let err = spec.validate().expect_err("friendly is not a label");
+        assert_eq!(
+            format!("{err:#}"),
+            "Invalid test #1 in tone: passing label 'friendly' is not listed in labels"
+        );
+
+        let yaml2 = r#"
+name: tone
+tests:
+  - type: label
+    model: gpt-4o-mini
+    labels: [polite, rude]
+    passing_labels: [polite]
+    messages:
+      - role: judge
+        content: "Classify the tone: {{response.text}}"
+"#;
+        assert!(serde_yaml::from_str::<EvalSpec>(yaml2).is_err());
+    }
+
+    #[test]
+    fn validate_multi_test_formula() {
+        let yaml = r#"
+name: quality
+tests:
+  - type: multi
+    name: Quality
+    calculate_output: "0.7 * correctness + 0.3 * stlye"
+    tests:
+      correctness:
+        type: string
+        input: "{{response.text}}"
+        eq: "{{answer}}"
+      style:
+        type: similarity
+        input: "{{response.text}}"
+        reference: "{{reference}}"
+        metric: cosine
+        pass_threshold: 0.8
+"#;
+        let spec: EvalSpec = serde_yaml::from_str(yaml).expect("parse yaml");
+        let err = spec.validate().expect_err("stlye is a typo");
+        assert_eq!(
+            format!("{err:#}"),
+            "Invalid test #1 in quality: calculate_output references unknown test 'stlye'"
+        );
+    }
 }
@@ -3,7 +3,10 @@ use regex::Regex;
 use serde_json::{Map, Value, json};
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::simple::{EvalSpec, SimpleFieldType, TestSpec};
+use crate::simple::{
+    EvalSpec, SimpleField, SimpleFieldType, SimpleMessage, SimpleMessageRole,
+    SimpleSimilarityMetric, TestSpec,
+};
 use xeval_openai::evals::*;
 
 impl EvalSpec {
@@ -13,40 +16,13 @@ impl EvalSpec {
         let mut required: Vec<String> = Vec::new();
 
         for (k, v) in &self.schema {
-            item_props.insert(k.clone(), type_to_json_schema(v));
-            required.push(k.clone());
-        }
-
-        // Collect extra item vars referenced by tests (e.g. {{answer}})
-        let mut extra_item_keys: BTreeSet<String> = BTreeSet::new();
-        for t in &self.tests {
-            match t {
-                TestSpec::String {
-                    input,
-                    eq,
-                    ne,
-                    like,
-                    ilike,
-                    ..
-                } => {
-                    collect_item_vars(input, &mut extra_item_keys);
-                    if let Some(x) = eq {
-                        collect_item_vars(x, &mut extra_item_keys);
-                    }
-                    if let Some(x) = ne {
-                        collect_item_vars(x, &mut extra_item_keys);
-                    }
-                    if let Some(x) = like {
-                        collect_item_vars(x, &mut extra_item_keys);
-                    }
-                    if let Some(x) = ilike {
-                        collect_item_vars(x, &mut extra_item_keys);
-                    }
-                }
+            item_props.insert(k.clone(), field_to_json_schema(v));
+            if !v.optional {
+                required.push(k.clone());
             }
         }
 
-        for k in extra_item_keys {
+        for k in self.implied_item_keys() {
             if !item_props.contains_key(&k) {
                 item_props.insert(k.clone(), json!({"type": "string"}));
                 required.push(k);
@@ -69,8 +45,7 @@ impl EvalSpec {
         let graders = self
             .tests
             .iter()
-            .enumerate()
-            .map(|(i, t)| string_test_to_grader(i, t))
+            .map(test_to_grader)
             .collect::<Result<Vec<_>>>()?;
 
         let eval = OpenAiEval {
@@ -88,9 +63,32 @@ impl EvalSpec {
 
         Ok(eval)
     }
+
+    /// Declared schema fields plus string fields implied by test templates,
+    /// i.e. everything a dataset item has to provide.
+    pub fn item_fields(&self) -> BTreeMap<String, SimpleField> {
+        let mut fields = self.schema.clone();
+        for k in self.implied_item_keys() {
+            fields
+                .entry(k)
+                .or_insert_with(|| SimpleFieldType::String.into());
+        }
+        fields
+    }
+
+    /// Collect extra item vars referenced by tests (e.g. {{answer}})
+    fn implied_item_keys(&self) -> BTreeSet<String> {
+        let mut keys: BTreeSet<String> = BTreeSet::new();
+        for t in &self.tests {
+            for template in test_templates(t) {
+                collect_item_vars(template, &mut keys);
+            }
+        }
+        keys
+    }
 }
 
-fn slug_like(s: &str) -> String {
+pub fn slug_like(s: &str) -> String {
     s.chars()
         .map(|c| {
             if c.is_ascii_alphanumeric() {
@@ -106,9 +104,89 @@ fn slug_like(s: &str) -> String {
 
 fn type_to_json_schema(t: &SimpleFieldType) -> Value {
     match t {
-        SimpleFieldType::String => json!({"type": "string"}),
-        SimpleFieldType::Number => json!({"type": "number"}),
-        SimpleFieldType::Boolean => json!({"type": "boolean"}),
+        SimpleFieldType::String => json!("string"),
+        SimpleFieldType::Number => json!("number"),
+        SimpleFieldType::Boolean => json!("boolean"),
+        SimpleFieldType::Array => json!("array"),
+        SimpleFieldType::Object => json!("object"),
+    }
+}
+
+fn field_to_json_schema(field: &SimpleField) -> Value {
+    let mut schema = Map::new();
+
+    let r#type = type_to_json_schema(&field.r#type);
+    if field.nullable {
+        schema.insert("type".into(), json!([r#type, "null"]));
+    } else {
+        schema.insert("type".into(), r#type);
+    }
+
+    if let Some(description) = &field.description {
+        schema.insert("description".into(), json!(description));
+    }
+
+    if let Some(values) = &field.r#enum {
+        let mut values: Vec<Value> = values.iter().map(|v| json!(v)).collect();
+        if field.nullable {
+            values.push(Value::Null);
+        }
+        schema.insert("enum".into(), Value::Array(values));
+    }
+
+    if let Some(items) = &field.items {
+        schema.insert("items".into(), field_to_json_schema(items));
+    }
+
+    if field.r#type == SimpleFieldType::Object {
+        let properties: Map<String, Value> = field
+            .properties
+            .iter()
+            .map(|(k, v)| (k.clone(), field_to_json_schema(v)))
+            .collect();
+        let required: Vec<&String> = field
+            .properties
+            .iter()
+            .filter(|(_, v)| !v.optional)
+            .map(|(k, _)| k)
+            .collect();
+        schema.insert("properties".into(), Value::Object(properties));
+        schema.insert("required".into(), json!(required));
+    }
+
+    Value::Object(schema)
+}
+
+fn test_templates(t: &TestSpec) -> Vec<&str> {
+    match t {
+        TestSpec::String {
+            input,
+            eq,
+            ne,
+            like,
+            ilike,
+            ..
+        } => {
+            let mut templates = vec![input.as_str()];
+            templates.extend(
+                [eq, ne, like, ilike]
+                    .into_iter()
+                    .filter_map(|x| x.as_deref()),
+            );
+            templates
+        }
+
+        TestSpec::Similarity {
+            input, reference, ..
+        } => vec![input.as_str(), reference.as_str()],
+
+        TestSpec::Score { messages, .. } | TestSpec::Label { messages, .. } => {
+            messages.iter().map(|m| m.content.as_str()).collect()
+        }
+
+        TestSpec::Python { .. } => vec![],
+
+        TestSpec::Multi { tests, .. } => tests.values().flat_map(test_templates).collect(),
     }
 }
 
@@ -126,7 +204,7 @@ fn collect_item_vars(template: &str, out: &mut BTreeSet<String>) {
     }
 }
 
-fn translate_template_placeholders(s: &str) -> String {
+pub fn translate_template_placeholders(s: &str) -> String {
     // Map tokens inside {{...}} to OpenAI expected paths
     // - {{response.text}} -> {{sample.output_text}}
     // - {{foo}} -> {{item.foo}}
@@ -151,7 +229,8 @@ fn translate_template_placeholders(s: &str) -> String {
     .into_owned()
 }
 
-fn string_test_to_grader(index: usize, t: &TestSpec) -> Result<OpenAiGrader> {
+/// Expects a validated test, see `EvalSpec::validate`.
+fn test_to_grader(t: &TestSpec) -> Result<OpenAiGrader> {
     match t {
         TestSpec::String {
             name,
@@ -185,6 +264,144 @@ fn string_test_to_grader(index: usize, t: &TestSpec) -> Result<OpenAiGrader> {
                 reference: translate_template_placeholders(&reference),
             }))
         }
+
+        TestSpec::Similarity {
+            name,
+            input,
+            reference,
+            metric,
+            pass_threshold,
+        } => {
+            let name = name
+                .clone()
+                .unwrap_or_else(|| "Text similarity grader".to_string());
+
+            Ok(OpenAiGrader::TextSimilarity(OpenAiGraderTextSimilarity {
+                r#type: OpenAiGraderTextSimilarityType,
+                name,
+                evaluation_metric: similarity_metric_to_openai(metric),
+                input: translate_template_placeholders(input),
+                reference: translate_template_placeholders(reference),
+                pass_threshold: *pass_threshold,
+            }))
+        }
+
+        TestSpec::Score {
+            name,
+            model,
+            range,
+            pass_threshold,
+            sampling_params,
+            messages,
+        } => {
+            let name = name
+                .clone()
+                .unwrap_or_else(|| "Score model grader".to_string());
+            Ok(OpenAiGrader::ScoreModel(OpenAiGraderScoreModel {
+                r#type: OpenAiGraderScoreModelType,
+                name,
+                model: model.clone(),
+                pass_threshold: *pass_threshold,
+                range: *range,
+                sampling_params: sampling_params.clone(),
+                input: messages.iter().map(message_to_model_input).collect(),
+            }))
+        }
+
+        TestSpec::Label {
+            name,
+            model,
+            labels,
+            passing_labels,
+            messages,
+        } => {
+            let name = name
+                .clone()
+                .unwrap_or_else(|| "Label model grader".to_string());
+
+            Ok(OpenAiGrader::LabelModel(OpenAiGraderLabelModel {
+                r#type: OpenAiGraderLabelModelType,
+                name,
+                model: model.clone(),
+                labels: labels.clone(),
+                passing_labels: passing_labels.clone(),
+                input: messages.iter().map(message_to_model_input).collect(),
+            }))
+        }
+
+        TestSpec::Python {
+            name,
+            source,
+            image_tag,
+            pass_threshold,
+            source_code,
+        } => {
+            let name = name.clone().unwrap_or_else(|| "Python grader".to_string());
+            let source_code = source_code
+                .clone()
+                .ok_or_else(|| anyhow!("python test source {source} was not loaded"))?;
+
+            Ok(OpenAiGrader::Python(OpenAiGraderPython {
+                r#type: OpenAiGraderPythonType,
+                name,
+                source: source_code,
+                image_tag: image_tag.clone(),
+                pass_threshold: *pass_threshold,
+            }))
+        }
+
+        TestSpec::Multi {
+            name,
+            calculate_output,
+            tests,
+        } => {
+            let graders = tests
+                .iter()
+                .map(|(key, t)| Ok((key.clone(), test_to_grader(t)?)))
+                .collect::<Result<BTreeMap<_, _>>>()?;
+
+            Ok(OpenAiGrader::Multi(OpenAiGraderMulti {
+                r#type: OpenAiGraderMultiType,
+                name: name.clone(),
+                graders,
+                calculate_output: calculate_output.clone(),
+            }))
+        }
+    }
+}
+
+fn message_to_model_input(message: &SimpleMessage) -> OpenAiModelInput {
+    let role = match message.role {
+        SimpleMessageRole::User => OpenAiMessageRole::User,
+        SimpleMessageRole::Assistant => OpenAiMessageRole::Assistant,
+        SimpleMessageRole::System => OpenAiMessageRole::System,
+        SimpleMessageRole::Developer => OpenAiMessageRole::Developer,
+    };
+
+    OpenAiModelInput {
+        r#type: OpenAiMessageType,
+        role,
+        content: OpenAiModelInputContent::String(translate_template_placeholders(&message.content)),
+    }
+}
+
+fn similarity_metric_to_openai(
+    metric: &SimpleSimilarityMetric,
+) -> OpenAiGraderTextSimilarityEvaluationMetric {
+    match metric {
+        SimpleSimilarityMetric::Cosine => OpenAiGraderTextSimilarityEvaluationMetric::Cosine,
+        SimpleSimilarityMetric::FuzzyMatch => {
+            OpenAiGraderTextSimilarityEvaluationMetric::FuzzyMatch
+        }
+        SimpleSimilarityMetric::Bleu => OpenAiGraderTextSimilarityEvaluationMetric::Bleu,
+        SimpleSimilarityMetric::Gleu => OpenAiGraderTextSimilarityEvaluationMetric::Gleu,
+        SimpleSimilarityMetric::Meteor => OpenAiGraderTextSimilarityEvaluationMetric::Meteor,
+        SimpleSimilarityMetric::Rouge1 => OpenAiGraderTextSimilarityEvaluationMetric::Rouge1,
+        SimpleSimilarityMetric::Rouge2 => OpenAiGraderTextSimilarityEvaluationMetric::Rouge2,
+        SimpleSimilarityMetric::Rouge3 => OpenAiGraderTextSimilarityEvaluationMetric::Rouge3,
+        SimpleSimilarityMetric::Rouge4 => OpenAiGraderTextSimilarityEvaluationMetric::Rouge4,
+        SimpleSimilarityMetric::Rouge5 => OpenAiGraderTextSimilarityEvaluationMetric::Rouge5,
+        SimpleSimilarityMetric::RougeL => OpenAiGraderTextSimilarityEvaluationMetric::RougeL,
     }
 }
 
@@ -275,4 +492,136 @@ tests:
         let json = to_string_pretty(&eval).unwrap();
         assert_snapshot!("openai_eval_math", json);
     }
+
+    #[test]
+    fn converts_similarity_test_to_text_similarity_grader() {
+        let yaml = r#"
+name: summary
+schema:
+  article: string
+tests:
+  - type: similarity
+    name: Summary matches reference
+    input: "{{response.text}}"
+    reference: "{{summary}}"
+    metric: rouge_l
+    pass_threshold: 0.6
+"#;
+        let spec: EvalSpec = serde_yaml::from_str(yaml).unwrap();
+        let eval = spec.to_openai_eval().unwrap();
+        let json = to_string_pretty(&eval.testing_criteria).unwrap();
+        assert_snapshot!("openai_eval_similarity_criteria", json);
+    }
+
+    #[test]
+    fn converts_score_test_to_score_model_grader() {
+        let yaml = r#"
+name: helpfulness
+schema:
+  question: string
+tests:
+  - type: score
+    model: gpt-4o-mini
+    range: [1, 5]
+    pass_threshold: 4
+    sampling_params:
+      temperature: 0
+    messages:
+      - role: system
+        content: Rate how helpful the answer is from 1 to 5.
+      - role: user
+        content: "Question: {{question}}\nAnswer: {{response.text}}"
+"#;
+        let spec: EvalSpec = serde_yaml::from_str(yaml).unwrap();
+        let eval = spec.to_openai_eval().unwrap();
+        let json = to_string_pretty(&eval.testing_criteria).unwrap();
+        assert_snapshot!("openai_eval_score_criteria", json);
+    }
+
+    #[test]
+    fn converts_label_test_to_label_model_grader() {
+        let yaml = r#"
+name: tone
+tests:
+  - type: label
+    name: Polite tone
+    model: gpt-4o-mini
+    labels: [polite, neutral, rude]
+    passing_labels: [polite, neutral]
+    messages:
+      - role: developer
+        content: Classify the tone of the reply.
+      - role: user
+        content: "{{response.text}}"
+"#;
+        let spec: EvalSpec = serde_yaml::from_str(yaml).unwrap();
+        let eval = spec.to_openai_eval().unwrap();
+        let json = to_string_pretty(&eval.testing_criteria).unwrap();
+        assert_snapshot!("openai_eval_label_criteria", json);
+    }
+
+    #[test]
+    fn converts_multi_test_to_multi_grader() {
+        let yaml = r#"
+name: quality
+tests:
+  - type: multi
+    name: Quality
+    calculate_output: "0.7 * correctness + 0.3 * style"
+    tests:
+      correctness:
+        type: string
+        name: Correct answer
+        input: "{{response.text}}"
+        eq: "{{answer}}"
+      style:
+        type: similarity
+        name: Matches reference style
+        input: "{{response.text}}"
+        reference: "{{reference}}"
+        metric: cosine
+        pass_threshold: 0.8
+"#;
+        let spec: EvalSpec = serde_yaml::from_str(yaml).unwrap();
+        let eval = spec.to_openai_eval().unwrap();
+        let json = to_string_pretty(&eval.testing_criteria).unwrap();
+        assert_snapshot!("openai_eval_multi_criteria", json);
+    }
+
+    #[test]
+    fn converts_rich_schema_fields_to_item_schema() {
+        let yaml = r#"
+name: chat
+schema:
+  question:
+    type: string
+    description: User question
+  tags:
+    type: array
+    items: string
+  tone:
+    type: string
+    enum: [formal, casual]
+    nullable: true
+  metadata:
+    type: object
+    optional: true
+    properties:
+      source: string
+      score:
+        type: number
+        optional: true
+tests:
+  - type: string
+    input: "{{response.text}}"
+    ilike: "%{{question}}%"
+"#;
+        let spec: EvalSpec = serde_yaml::from_str(yaml).unwrap();
+        let eval = spec.to_openai_eval().unwrap();
+        let OpenAiDataSourceConfig::Custom(config) = &eval.data_source_config else {
+            panic!("expected custom data source config");
+        };
+        let json = to_string_pretty(&config.schema["properties"]["item"]).unwrap();
+        assert_snapshot!("openai_eval_rich_item_schema", json);
+    }
 }